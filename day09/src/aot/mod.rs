@@ -0,0 +1,312 @@
+use std::collections::BTreeSet;
+
+use crate::disasm::{disassemble, Instruction, Line};
+use crate::{Opcode, ParameterMode};
+
+/// Translate an Intcode image into the source of a Rust module.
+///
+/// The module exposes `execute_program` with the same signature and I/O contract as
+/// [`crate::execute_program`]. Straight-line code is translated into native statements and
+/// jumps with a known target into native control flow. Before executing an instruction, the
+/// translated code checks that its memory still holds the original image. Whatever was
+/// overwritten, or isn't known to be code, such as targets of computed jumps, is executed by
+/// the interpreter until it reaches translated code again.
+///
+/// The module expects the `day09` crate to be available.
+///
+/// ## Examples
+/// ```
+/// # use day09::aot::translate;
+/// let source = translate(&[1101, 20, 22, 5, 104, 0, 99]);
+/// assert!(source.contains("pub fn execute_program<R, W>"));
+/// assert!(source.contains("let target = 5;"));
+/// ```
+pub fn translate(program: &[i128]) -> String {
+	let blocks = find_blocks(program);
+
+	let mut arms = String::new();
+	for block in blocks.iter() {
+		arms.push_str(&format!("\t\t\t{} => {{\n", block[0].address));
+		for instruction in block.iter() {
+			arms.push_str(&format!(
+				"\t\t\t\t// {:04}: {}\n",
+				instruction.address, instruction
+			));
+			match translate_instruction(instruction) {
+				Some(statements) => {
+					arms.push_str(&indent(&guard(program, instruction), 4));
+					arms.push_str(&indent(&statements, 4));
+				}
+				None => {
+					arms.push_str(&indent(&fallback(instruction.address), 4));
+				}
+			}
+		}
+		let last = block.last().expect("Blocks are never empty");
+		if !ends_control_flow(last) {
+			arms.push_str(&format!(
+				"\t\t\t\tidx = {};\n\t\t\t\tcontinue;\n",
+				last.address + last.size()
+			));
+		}
+		arms.push_str("\t\t\t}\n");
+	}
+
+	format!(
+		"// Translated from an Intcode image by day09::aot.
+use std::io::{{BufRead, Write}};
+
+#[allow(unused_imports)]
+use ::day09::{{IntcodeError, Opcode, ParameterMode, State}};
+
+#[allow(dead_code)]
+fn address(value: i128) -> Result<usize, IntcodeError> {{
+	if value < 0 {{
+		Err(IntcodeError::InvalidAddress(value))
+	}} else {{
+		Ok(value as usize)
+	}}
+}}
+
+#[allow(clippy::all, unused_variables, unused_mut, unreachable_code)]
+pub fn execute_program<R, W>(
+	program: &mut [i128],
+	mut reader: R,
+	mut writer: W,
+) -> Result<(), IntcodeError>
+where
+	R: BufRead,
+	W: Write,
+{{
+	let mut idx: usize = 0;
+	let mut state = State::new();
+	let mut interpret = false;
+
+	loop {{
+		if interpret {{
+			interpret = false;
+			if let Opcode::Halt =
+				::day09::execute_step(program, &mut idx, &mut state, &mut reader, &mut writer)?
+			{{
+				return Ok(());
+			}}
+			continue;
+		}}
+
+		match idx {{
+{}			_ => interpret = true,
+		}}
+	}}
+}}
+",
+		arms
+	)
+}
+
+/// Split the program's instructions into blocks that are entered at the top and left at the bottom.
+fn find_blocks(program: &[i128]) -> Vec<Vec<Instruction>> {
+	let lines = disassemble(program);
+
+	// Jump targets we know about start a block.
+	let mut leaders = BTreeSet::new();
+	leaders.insert(0);
+	for line in lines.iter() {
+		if let Line::Instruction(instruction) = line {
+			if let Some(target) = static_jump_target(instruction) {
+				leaders.insert(target);
+			}
+		}
+	}
+
+	let mut blocks: Vec<Vec<Instruction>> = Vec::new();
+	let mut current: Vec<Instruction> = Vec::new();
+	for line in lines {
+		let instruction = match line {
+			Line::Instruction(instruction) => instruction,
+			Line::Data { .. } => {
+				if !current.is_empty() {
+					blocks.push(current.split_off(0));
+				}
+				continue;
+			}
+		};
+		if leaders.contains(&instruction.address) && !current.is_empty() {
+			blocks.push(current.split_off(0));
+		}
+		let ends_block =
+			ends_control_flow(&instruction) || translate_instruction(&instruction).is_none();
+		current.push(instruction);
+		if ends_block {
+			blocks.push(current.split_off(0));
+		}
+	}
+	if !current.is_empty() {
+		blocks.push(current);
+	}
+	blocks
+}
+
+fn static_jump_target(instruction: &Instruction) -> Option<usize> {
+	match instruction.opcode {
+		Opcode::JumpZero | Opcode::JumpNonZero
+			if instruction.modes[1] == ParameterMode::Immediate && instruction.params[1] >= 0 =>
+		{
+			Some(instruction.params[1] as usize)
+		}
+		_ => None,
+	}
+}
+
+/// Whether execution never continues with the next instruction.
+fn ends_control_flow(instruction: &Instruction) -> bool {
+	matches!(
+		instruction.opcode,
+		Opcode::Halt | Opcode::JumpZero | Opcode::JumpNonZero
+	)
+}
+
+fn indent(code: &str, level: usize) -> String {
+	code.lines()
+		.map(|line| format!("{}{}\n", "\t".repeat(level), line))
+		.collect()
+}
+
+/// Hand the instruction over to the interpreter if its memory changed.
+fn guard(program: &[i128], instruction: &Instruction) -> String {
+	let cells = program[instruction.address..instruction.address + instruction.size()]
+		.iter()
+		.map(|value| value.to_string())
+		.collect::<Vec<String>>();
+	format!(
+		"if program.get({}..{}) != Some(&[{}][..]) {{\n{}}}\n",
+		instruction.address,
+		instruction.address + instruction.size(),
+		cells.join(", "),
+		indent(&fallback(instruction.address), 1)
+	)
+}
+
+fn fallback(address: usize) -> String {
+	format!("idx = {};\ninterpret = true;\ncontinue;\n", address)
+}
+
+/// An expression for the value a parameter refers to.
+fn read(param: i128, mode: ParameterMode) -> Option<String> {
+	match mode {
+		ParameterMode::Immediate => Some(param.to_string()),
+		ParameterMode::Position if param >= 0 => Some(format!(
+			"program.get({0}).copied().ok_or(IntcodeError::InvalidAddress({0}))?",
+			param
+		)),
+		ParameterMode::Position => None,
+		ParameterMode::Relative => Some(format!(
+			"{{ let a = address(state.relative_base() + ({}))?; program.get(a).copied().ok_or(IntcodeError::InvalidAddress(a as i128))? }}",
+			param
+		)),
+	}
+}
+
+/// An expression for the address a parameter writes to.
+fn target(param: i128, mode: ParameterMode) -> Option<String> {
+	match mode {
+		ParameterMode::Position if param >= 0 => Some(param.to_string()),
+		ParameterMode::Relative => Some(format!("address(state.relative_base() + ({}))?", param)),
+		_ => None,
+	}
+}
+
+fn mode_path(mode: ParameterMode) -> &'static str {
+	match mode {
+		ParameterMode::Position => "ParameterMode::Position",
+		ParameterMode::Immediate => "ParameterMode::Immediate",
+		ParameterMode::Relative => "ParameterMode::Relative",
+	}
+}
+
+/// Native statements for an instruction, or `None` if only the interpreter can handle it.
+fn translate_instruction(instruction: &Instruction) -> Option<String> {
+	let params = &instruction.params;
+	let modes = &instruction.modes;
+	let store = |value: String| -> Option<String> {
+		Some(format!(
+			"{{\n\tlet value = {};\n\tlet target = {};\n\t*program.get_mut(target).ok_or(IntcodeError::InvalidAddress(target as i128))? = value;\n}}\n",
+			value,
+			target(params[2], modes[2])?
+		))
+	};
+
+	match instruction.opcode {
+		Opcode::Add => store(format!(
+			"{} + {}",
+			read(params[0], modes[0])?,
+			read(params[1], modes[1])?
+		)),
+		Opcode::Mult => store(format!(
+			"{} * {}",
+			read(params[0], modes[0])?,
+			read(params[1], modes[1])?
+		)),
+		Opcode::CompareLt => store(format!(
+			"({} < {}) as i128",
+			read(params[0], modes[0])?,
+			read(params[1], modes[1])?
+		)),
+		Opcode::CompareEq => store(format!(
+			"({} == {}) as i128",
+			read(params[0], modes[0])?,
+			read(params[1], modes[1])?
+		)),
+		Opcode::AdjustRelBase => Some(format!(
+			"{{\n\tlet adjustment = {};\n\tstate.adjust_relative_base(adjustment);\n}}\n",
+			read(params[0], modes[0])?
+		)),
+		Opcode::Input => {
+			target(params[0], modes[0])?;
+			Some(format!(
+				"::day09::input(program, {}, &[{}], &mut reader, &state)?;\n",
+				instruction.address,
+				mode_path(modes[0])
+			))
+		}
+		Opcode::Output => Some(format!(
+			"{{\n\tlet value = {};\n\twriteln!(writer, \"{{}}\", value)?;\n}}\n",
+			read(params[0], modes[0])?
+		)),
+		Opcode::JumpZero | Opcode::JumpNonZero => {
+			let jump = match modes[1] {
+				ParameterMode::Immediate if params[1] < 0 => return None,
+				ParameterMode::Immediate => params[1].to_string(),
+				mode => format!("address({})?", read(params[1], mode)?),
+			};
+			let comparison = if instruction.opcode == Opcode::JumpZero {
+				"=="
+			} else {
+				"!="
+			};
+			match modes[0] {
+				// A constant condition either always or never jumps.
+				ParameterMode::Immediate => {
+					let taken = (params[0] == 0) == (instruction.opcode == Opcode::JumpZero);
+					let next = if taken {
+						"target".to_string()
+					} else {
+						(instruction.address + instruction.size()).to_string()
+					};
+					// The target is checked even if the jump isn't taken, like the interpreter does.
+					Some(format!(
+						"{{\n\tlet target = {};\n\tidx = {};\n\tcontinue;\n}}\n",
+						jump, next
+					))
+				}
+				mode => Some(format!(
+					"{{\n\tlet target = {};\n\tif {} {} 0 {{\n\t\tidx = target;\n\t\tcontinue;\n\t}}\n\tidx = {};\n\tcontinue;\n}}\n",
+					jump,
+					read(params[0], mode)?,
+					comparison,
+					instruction.address + instruction.size()
+				)),
+			}
+		}
+		Opcode::Halt => Some("return Ok(());\n".to_string()),
+	}
+}
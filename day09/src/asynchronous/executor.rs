@@ -0,0 +1,175 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+/// Every task still waits for something that no other task is going to provide.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Stalled {
+	pub pending: usize,
+}
+
+impl fmt::Display for Stalled {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{} tasks are waiting but none can make progress",
+			self.pending
+		)
+	}
+}
+
+impl Error for Stalled {}
+
+/// Remembers that a task was woken, so the executor polls it again.
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+	fn wake(self: Arc<Self>) {
+		self.0.store(true, Ordering::SeqCst);
+	}
+}
+
+struct Task<'a> {
+	future: Pin<Box<dyn Future<Output = ()> + 'a>>,
+	woken: Arc<Flag>,
+}
+
+/// The result of a spawned task, once it finished.
+#[derive(Debug)]
+pub struct JoinHandle<T> {
+	result: Rc<RefCell<Option<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+	/// Take the task's result. `None` if it hasn't finished or the result was already taken.
+	pub fn take(&self) -> Option<T> {
+		self.result.borrow_mut().take()
+	}
+}
+
+/// Runs futures on the current thread, taking turns whenever one of them has to wait.
+///
+/// ## Examples
+/// ```
+/// # use day09::asynchronous::{channel, iter, run, LocalExecutor};
+/// let (mut doubler, mut doubled) = (vec![3, 9, 1002, 9, 2, 9, 4, 9, 99, 0], Vec::new());
+/// let (mut echo, mut echoed) = (vec![3, 5, 4, 5, 99, 0], Vec::new());
+/// let (sender, receiver) = channel();
+///
+/// let mut executor = LocalExecutor::new();
+/// let waiting = executor.spawn(run(&mut doubler, receiver, &mut doubled));
+/// let starved = executor.spawn(run(&mut echo, iter(vec![]), &mut echoed));
+/// assert!(executor.run().is_err());
+///
+/// // Give the waiting machine its input and let it finish.
+/// sender.send(21).unwrap();
+/// executor.run().unwrap();
+/// assert!(waiting.take().unwrap().is_ok());
+/// assert!(starved.take().unwrap().is_err());
+/// drop(executor);
+/// assert_eq!(doubled, [42]);
+/// ```
+#[derive(Default)]
+pub struct LocalExecutor<'a> {
+	tasks: Vec<Task<'a>>,
+}
+
+impl<'a> LocalExecutor<'a> {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Add a future to run. Nothing happens until [`LocalExecutor::run`].
+	pub fn spawn<F>(&mut self, future: F) -> JoinHandle<F::Output>
+	where
+		F: Future + 'a,
+		F::Output: 'a,
+	{
+		let result = Rc::new(RefCell::new(None));
+		let slot = Rc::clone(&result);
+		self.tasks.push(Task {
+			future: Box::pin(async move {
+				let output = future.await;
+				*slot.borrow_mut() = Some(output);
+			}),
+			// Every task gets polled at least once.
+			woken: Arc::new(Flag(AtomicBool::new(true))),
+		});
+		JoinHandle { result }
+	}
+
+	pub fn len(&self) -> usize {
+		self.tasks.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.tasks.is_empty()
+	}
+
+	/// Poll the tasks in turn until all of them have finished.
+	///
+	/// Fails if the remaining tasks all wait without anyone to wake them. They are kept,
+	/// so running again after providing what they wait for picks up where they stopped.
+	pub fn run(&mut self) -> Result<(), Stalled> {
+		while !self.tasks.is_empty() {
+			let mut progressed = false;
+			let mut idx = 0;
+			while idx < self.tasks.len() {
+				let task = &mut self.tasks[idx];
+				if !task.woken.0.swap(false, Ordering::SeqCst) {
+					idx += 1;
+					continue;
+				}
+				progressed = true;
+				let waker = Waker::from(Arc::clone(&task.woken));
+				let mut cx = Context::from_waker(&waker);
+				match task.future.as_mut().poll(&mut cx) {
+					Poll::Ready(()) => {
+						self.tasks.remove(idx);
+					}
+					Poll::Pending => idx += 1,
+				}
+			}
+			if !progressed {
+				return Err(Stalled {
+					pending: self.tasks.len(),
+				});
+			}
+		}
+		Ok(())
+	}
+
+	/// Run a single future to completion, along with any tasks spawned before.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::asynchronous::{iter, run, LocalExecutor};
+	/// let mut output = Vec::new();
+	/// let result = LocalExecutor::new().block_on(run(&mut [3, 0, 4, 0, 99], iter(vec![7]), &mut output));
+	/// assert!(result.unwrap().is_ok());
+	/// assert_eq!(output, [7]);
+	/// ```
+	pub fn block_on<F>(mut self, future: F) -> Result<F::Output, Stalled>
+	where
+		F: Future + 'a,
+		F::Output: 'a,
+	{
+		let handle = self.spawn(future);
+		self.run()?;
+		Ok(handle.take().expect("Finished tasks leave their result"))
+	}
+}
+
+impl fmt::Debug for LocalExecutor<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LocalExecutor")
+			.field("tasks", &self.tasks.len())
+			.finish()
+	}
+}
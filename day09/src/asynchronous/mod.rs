@@ -0,0 +1,295 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+use crate::{execute_step, parse_instruction, IntcodeError, Opcode, State};
+
+mod executor;
+
+pub use executor::{JoinHandle, LocalExecutor, Stalled};
+
+/// A source of values that may not be available yet.
+pub trait Stream {
+	type Item;
+
+	/// The next value, `Ready(None)` once there won't be any more.
+	///
+	/// If the value isn't available yet, `cx`'s waker is woken once it might be.
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+/// A destination for values that may not be able to take them right away.
+pub trait Sink<T> {
+	/// Whether the sink can take a value. Once it says so, the next `start_send` must succeed.
+	fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+
+	fn start_send(self: Pin<&mut Self>, item: T) -> io::Result<()>;
+}
+
+impl<S: Stream + Unpin + ?Sized> Stream for &mut S {
+	type Item = S::Item;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Pin::new(&mut **self).poll_next(cx)
+	}
+}
+
+impl<T, S: Sink<T> + Unpin + ?Sized> Sink<T> for &mut S {
+	fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut **self).poll_ready(cx)
+	}
+
+	fn start_send(mut self: Pin<&mut Self>, item: T) -> io::Result<()> {
+		Pin::new(&mut **self).start_send(item)
+	}
+}
+
+impl<T: Unpin> Sink<T> for Vec<T> {
+	fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn start_send(self: Pin<&mut Self>, item: T) -> io::Result<()> {
+		self.get_mut().push(item);
+		Ok(())
+	}
+}
+
+/// A stream of values that are all available right away.
+#[derive(Clone, Debug)]
+pub struct Iter<I> {
+	inner: I,
+}
+
+impl<I: Iterator + Unpin> Stream for Iter<I> {
+	type Item = I::Item;
+
+	fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Poll::Ready(self.inner.next())
+	}
+}
+
+pub fn iter<I: IntoIterator>(values: I) -> Iter<I::IntoIter> {
+	Iter {
+		inner: values.into_iter(),
+	}
+}
+
+#[derive(Debug)]
+struct Channel<T> {
+	values: VecDeque<T>,
+	senders: usize,
+	receiver_alive: bool,
+	waker: Option<Waker>,
+}
+
+/// Create an unbounded channel for tasks on the same thread.
+///
+/// The receiving end is a [`Stream`] that ends once all senders are gone,
+/// the sending ends are [`Sink`]s that fail once the receiver is gone.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+	let channel = Rc::new(RefCell::new(Channel {
+		values: VecDeque::new(),
+		senders: 1,
+		receiver_alive: true,
+		waker: None,
+	}));
+	(
+		Sender {
+			channel: Rc::clone(&channel),
+		},
+		Receiver { channel },
+	)
+}
+
+#[derive(Debug)]
+pub struct Sender<T> {
+	channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Sender<T> {
+	/// Send a value without waiting, which works as long as the receiver exists.
+	pub fn send(&self, value: T) -> io::Result<()> {
+		let mut channel = self.channel.borrow_mut();
+		if !channel.receiver_alive {
+			return Err(io::Error::new(
+				io::ErrorKind::BrokenPipe,
+				"Receiver is gone",
+			));
+		}
+		channel.values.push_back(value);
+		if let Some(waker) = channel.waker.take() {
+			waker.wake();
+		}
+		Ok(())
+	}
+}
+
+impl<T> Clone for Sender<T> {
+	fn clone(&self) -> Self {
+		self.channel.borrow_mut().senders += 1;
+		Self {
+			channel: Rc::clone(&self.channel),
+		}
+	}
+}
+
+impl<T> Drop for Sender<T> {
+	fn drop(&mut self) {
+		let mut channel = self.channel.borrow_mut();
+		channel.senders -= 1;
+		if channel.senders == 0 {
+			// The receiver has to learn that the stream ended.
+			if let Some(waker) = channel.waker.take() {
+				waker.wake();
+			}
+		}
+	}
+}
+
+impl<T> Sink<T> for Sender<T> {
+	fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn start_send(self: Pin<&mut Self>, item: T) -> io::Result<()> {
+		self.send(item)
+	}
+}
+
+#[derive(Debug)]
+pub struct Receiver<T> {
+	channel: Rc<RefCell<Channel<T>>>,
+}
+
+impl<T> Receiver<T> {
+	/// Take a value if there is one, without waiting.
+	pub fn try_recv(&self) -> Option<T> {
+		self.channel.borrow_mut().values.pop_front()
+	}
+}
+
+impl<T> Drop for Receiver<T> {
+	fn drop(&mut self) {
+		self.channel.borrow_mut().receiver_alive = false;
+	}
+}
+
+impl<T> Stream for Receiver<T> {
+	type Item = T;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+		let mut channel = self.channel.borrow_mut();
+		match channel.values.pop_front() {
+			Some(value) => Poll::Ready(Some(value)),
+			None if channel.senders == 0 => Poll::Ready(None),
+			None => {
+				channel.waker = Some(cx.waker().clone());
+				Poll::Pending
+			}
+		}
+	}
+}
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+	poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+async fn send<T, K: Sink<T> + Unpin>(sink: &mut K, item: T) -> io::Result<()> {
+	poll_fn(|cx| Pin::new(&mut *sink).poll_ready(cx)).await?;
+	Pin::new(sink).start_send(item)
+}
+
+/// Whether the instruction at `idx` reads input. Broken instructions are left to the interpreter.
+fn wants_input(program: &[i128], idx: usize) -> bool {
+	match program.get(idx) {
+		Some(value) if *value >= 0 => {
+			matches!(parse_instruction(*value as u128), Ok((Opcode::Input, _)))
+		}
+		_ => false,
+	}
+}
+
+/// Execute an Intcode program, reading values from `input` and writing them to `output`.
+///
+/// Waiting for input or for room in `output` suspends the program rather than blocking the thread.
+/// If `input` ends while the program wants more, it fails with [`IntcodeError::EndOfInput`].
+///
+/// ## Examples
+/// A feedback loop of amplifiers, all running on one thread.
+/// ```
+/// # use day09::asynchronous::{channel, run, LocalExecutor};
+/// let program = vec![
+/// 	3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+/// 	1005, 28, 6, 99, 0, 0, 5,
+/// ];
+///
+/// let (mut senders, mut receivers) = (Vec::new(), Vec::new());
+/// for phase in [9, 8, 7, 6, 5].iter() {
+/// 	let (sender, receiver) = channel();
+/// 	sender.send(*phase).unwrap();
+/// 	senders.push(sender);
+/// 	receivers.push(receiver);
+/// }
+/// senders[0].send(0).unwrap();
+/// // The last amplifier feeds the first one.
+/// senders.rotate_left(1);
+///
+/// let mut executor = LocalExecutor::new();
+/// let handles = receivers
+/// 	.into_iter()
+/// 	.zip(senders)
+/// 	.map(|(mut input, output)| {
+/// 		let mut memory = program.clone();
+/// 		executor.spawn(async move {
+/// 			run(&mut memory, &mut input, output).await?;
+/// 			// Keep the input open, the last amplifier's final signal ends up here.
+/// 			Ok::<_, day09::IntcodeError>(input)
+/// 		})
+/// 	})
+/// 	.collect::<Vec<_>>();
+/// executor.run().unwrap();
+///
+/// let first_input = handles[0].take().unwrap().unwrap();
+/// assert_eq!(first_input.try_recv(), Some(139629729));
+/// ```
+pub async fn run<S, K>(
+	program: &mut [i128],
+	mut input: S,
+	mut output: K,
+) -> Result<(), IntcodeError>
+where
+	S: Stream<Item = i128> + Unpin,
+	K: Sink<i128> + Unpin,
+{
+	let mut idx: usize = 0;
+	let mut state = State::new();
+
+	loop {
+		let mut line = Vec::new();
+		if wants_input(program, idx) {
+			match next(&mut input).await {
+				Some(value) => line = format!("{}\n", value).into_bytes(),
+				None => return Err(IntcodeError::EndOfInput),
+			}
+		}
+
+		let mut written = Vec::new();
+		let opcode = execute_step(program, &mut idx, &mut state, &mut &line[..], &mut written)?;
+		if !written.is_empty() {
+			let text = String::from_utf8_lossy(&written);
+			let value = text
+				.trim()
+				.parse::<i128>()
+				.map_err(|_| IntcodeError::InvalidOutput(text.to_string()))?;
+			send(&mut output, value).await?;
+		}
+		if opcode == Opcode::Halt {
+			return Ok(());
+		}
+	}
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use crate::disasm::{disassemble, reachable, Instruction, Line};
+use crate::{execute_step, fetch, parse_instruction, parse_parameter, IntcodeError, Opcode, State};
+
+/// How often a conditional jump went either way.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct BranchOutcomes {
+	pub taken: u64,
+	pub not_taken: u64,
+}
+
+/// Executed addresses and branch outcomes, collected over one or more runs of a program.
+///
+/// ## Examples
+/// ```
+/// # use day09::coverage::{execute_program_with_coverage, Coverage};
+/// // Jump past the `out` if the input is zero.
+/// let program = [3, 9, 1006, 9, 7, 4, 9, 99, 0, 0];
+/// let mut coverage = Coverage::new();
+///
+/// let mut memory = program.to_vec();
+/// execute_program_with_coverage(&mut memory, "0".as_bytes(), vec![], &mut coverage).unwrap();
+/// assert_eq!(coverage.hits(5), 0);
+/// assert_eq!(coverage.branch(2).unwrap().taken, 1);
+///
+/// let mut memory = program.to_vec();
+/// execute_program_with_coverage(&mut memory, "1".as_bytes(), vec![], &mut coverage).unwrap();
+/// assert_eq!(coverage.hits(5), 1);
+/// assert_eq!(coverage.branch(2).unwrap().not_taken, 1);
+///
+/// let summary = coverage.summary(&program);
+/// assert_eq!((summary.covered_instructions, summary.instructions), (4, 4));
+/// assert_eq!((summary.covered_branch_outcomes, summary.branch_outcomes), (2, 2));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Coverage {
+	executed: BTreeMap<usize, u64>,
+	branches: BTreeMap<usize, BranchOutcomes>,
+}
+
+impl Coverage {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// How often the instruction at `address` was executed.
+	pub fn hits(&self, address: usize) -> u64 {
+		*self.executed.get(&address).unwrap_or(&0)
+	}
+
+	/// Outcomes of the conditional jump at `address`, if it was ever executed.
+	pub fn branch(&self, address: usize) -> Option<BranchOutcomes> {
+		self.branches.get(&address).copied()
+	}
+
+	/// All executed addresses in ascending order.
+	pub fn executed_addresses(&self) -> impl Iterator<Item = usize> + '_ {
+		self.executed.keys().copied()
+	}
+
+	/// Add the results of another run to ours.
+	pub fn merge(&mut self, other: &Coverage) {
+		for (address, hits) in other.executed.iter() {
+			*self.executed.entry(*address).or_insert(0) += hits;
+		}
+		for (address, outcomes) in other.branches.iter() {
+			let ours = self.branches.entry(*address).or_default();
+			ours.taken += outcomes.taken;
+			ours.not_taken += outcomes.not_taken;
+		}
+	}
+
+	/// Compare our results with the code in a program image: the instructions control flow can
+	/// reach, see [`reachable`], and the ones that were executed.
+	///
+	/// Pass the image as it was before execution, a program may overwrite its own code.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::coverage::{execute_program_with_coverage, Coverage};
+	/// // The `hlt` at 3 is jumped over, so it's data rather than code.
+	/// let program = [1105, 1, 4, 99, 99];
+	/// let mut coverage = Coverage::new();
+	/// execute_program_with_coverage(&mut program.clone(), "".as_bytes(), vec![], &mut coverage).unwrap();
+	/// let summary = coverage.summary(&program);
+	/// assert_eq!((summary.covered_instructions, summary.instructions), (2, 2));
+	/// ```
+	pub fn summary(&self, program: &[i128]) -> Summary {
+		let mut summary = Summary::default();
+		let mut code = reachable(program);
+		code.extend(self.executed_addresses());
+		for address in code {
+			if let Some(instruction) = Instruction::decode(program, address) {
+				summary.instructions += 1;
+				if self.hits(instruction.address) > 0 {
+					summary.covered_instructions += 1;
+				}
+				if let Opcode::JumpZero | Opcode::JumpNonZero = instruction.opcode {
+					summary.branch_outcomes += 2;
+					let outcomes = self.branch(instruction.address).unwrap_or_default();
+					summary.covered_branch_outcomes +=
+						(outcomes.taken > 0) as usize + (outcomes.not_taken > 0) as usize;
+				}
+			}
+		}
+		summary
+	}
+
+	/// Disassemble a program and annotate every line with its execution count and branch outcomes.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::coverage::{execute_program_with_coverage, Coverage};
+	/// let program = [1105, 1, 4, 99, 99];
+	/// let mut coverage = Coverage::new();
+	/// execute_program_with_coverage(&mut program.clone(), "".as_bytes(), vec![], &mut coverage).unwrap();
+	///
+	/// let annotated = coverage.annotate(&program);
+	/// let mut lines = annotated.lines();
+	/// assert_eq!(lines.next(), Some("       1 | 0000: jnz 1, 4 ; taken 1, not taken 0"));
+	/// assert_eq!(lines.next(), Some("       - | 0003: hlt"));
+	/// assert_eq!(lines.next(), Some("       1 | 0004: hlt"));
+	/// ```
+	pub fn annotate(&self, program: &[i128]) -> String {
+		let mut annotated = String::new();
+		for line in disassemble(program) {
+			let hits = match self.hits(line.address()) {
+				0 => "-".to_string(),
+				n => n.to_string(),
+			};
+			annotated.push_str(&format!("{:>8} | {}", hits, line));
+			if let Line::Instruction(instruction) = &line {
+				if let Opcode::JumpZero | Opcode::JumpNonZero = instruction.opcode {
+					let outcomes = self.branch(instruction.address).unwrap_or_default();
+					annotated.push_str(&format!(
+						" ; taken {}, not taken {}",
+						outcomes.taken, outcomes.not_taken
+					));
+				}
+			}
+			annotated.push('\n');
+		}
+		annotated
+	}
+
+	fn record(&mut self, address: usize, branch_taken: Option<bool>) {
+		*self.executed.entry(address).or_insert(0) += 1;
+		if let Some(taken) = branch_taken {
+			let outcomes = self.branches.entry(address).or_default();
+			if taken {
+				outcomes.taken += 1;
+			} else {
+				outcomes.not_taken += 1;
+			}
+		}
+	}
+}
+
+/// How much of a program's code was covered.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Summary {
+	pub instructions: usize,
+	pub covered_instructions: usize,
+	/// Each conditional jump has two outcomes.
+	pub branch_outcomes: usize,
+	pub covered_branch_outcomes: usize,
+}
+
+impl Summary {
+	pub fn instruction_percentage(&self) -> f64 {
+		percentage(self.covered_instructions, self.instructions)
+	}
+
+	pub fn branch_percentage(&self) -> f64 {
+		percentage(self.covered_branch_outcomes, self.branch_outcomes)
+	}
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+	if total == 0 {
+		100.0
+	} else {
+		part as f64 * 100.0 / total as f64
+	}
+}
+
+impl fmt::Display for Summary {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"Instructions: {}/{} ({:.1}%), branch outcomes: {}/{} ({:.1}%)",
+			self.covered_instructions,
+			self.instructions,
+			self.instruction_percentage(),
+			self.covered_branch_outcomes,
+			self.branch_outcomes,
+			self.branch_percentage()
+		)
+	}
+}
+
+/// Execute an Intcode program, adding the addresses it executes and the branches it takes to `coverage`.
+pub fn execute_program_with_coverage<R, W>(
+	program: &mut [i128],
+	mut reader: R,
+	mut writer: W,
+	coverage: &mut Coverage,
+) -> Result<(), IntcodeError>
+where
+	R: BufRead,
+	W: Write,
+{
+	let mut idx: usize = 0;
+	let mut state = State::new();
+
+	loop {
+		if let Opcode::Halt = execute_step_with_coverage(
+			program,
+			&mut idx,
+			&mut state,
+			&mut reader,
+			&mut writer,
+			coverage,
+		)? {
+			return Ok(());
+		};
+	}
+}
+
+/// Execute a single step and record it in `coverage`.
+pub fn execute_step_with_coverage<R, W>(
+	program: &mut [i128],
+	idx: &mut usize,
+	state: &mut State,
+	reader: &mut R,
+	writer: &mut W,
+	coverage: &mut Coverage,
+) -> Result<Opcode, IntcodeError>
+where
+	R: BufRead,
+	W: Write,
+{
+	let address = *idx;
+	let branch_taken = branch_condition(program, address, state)?;
+	let opcode = execute_step(program, idx, state, reader, writer)?;
+	coverage.record(address, branch_taken);
+	Ok(opcode)
+}
+
+/// Whether the conditional jump at `address` will be taken, or `None` if it isn't a conditional jump.
+fn branch_condition(
+	program: &[i128],
+	address: usize,
+	state: &State,
+) -> Result<Option<bool>, IntcodeError> {
+	let instruction = fetch(program, address)?;
+	if instruction < 0 {
+		return Ok(None);
+	}
+	let (opcode, modes) = match parse_instruction(instruction as u128) {
+		Ok(decoded) => decoded,
+		// Let the actual step report the error.
+		Err(_) => return Ok(None),
+	};
+	let condition = || parse_parameter(fetch(program, address + 1)?, modes.first(), program, state);
+	match opcode {
+		Opcode::JumpZero => Ok(Some(condition()? == 0)),
+		Opcode::JumpNonZero => Ok(Some(condition()? != 0)),
+		_ => Ok(None),
+	}
+}
@@ -0,0 +1,220 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::{parse_instruction, Opcode, ParameterMode};
+
+/// A single decoded instruction.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Instruction {
+	pub address: usize,
+	pub opcode: Opcode,
+	pub modes: Vec<ParameterMode>,
+	pub params: Vec<i128>,
+}
+
+impl Instruction {
+	/// Decode the instruction at `address`.
+	///
+	/// Returns `None` if the value there isn't a valid instruction or its parameters run past the end of memory.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::{disasm::Instruction, Opcode};
+	/// let program = [1002, 4, 3, 4, 33];
+	/// let instruction = Instruction::decode(&program, 0).unwrap();
+	/// assert_eq!(instruction.opcode, Opcode::Mult);
+	/// assert_eq!(instruction.params, [4, 3, 4]);
+	/// assert_eq!(instruction.to_string(), "mul [4], 3, [4]");
+	/// assert_eq!(Instruction::decode(&program, 4), None);
+	/// ```
+	pub fn decode(program: &[i128], address: usize) -> Option<Self> {
+		let instruction = *program.get(address)?;
+		if instruction < 0 {
+			return None;
+		}
+		let (opcode, modes) = parse_instruction(instruction as u128).ok()?;
+		let params = program
+			.get(address + 1..address + 1 + modes.len())?
+			.to_vec();
+		Some(Self {
+			address,
+			opcode,
+			modes,
+			params,
+		})
+	}
+
+	/// Number of memory cells the instruction takes up.
+	pub fn size(&self) -> usize {
+		1 + self.params.len()
+	}
+}
+
+/// Short name of an opcode, as used in disassembly.
+pub fn mnemonic(opcode: Opcode) -> String {
+	match opcode {
+		Opcode::Add => "add".to_string(),
+		Opcode::Mult => "mul".to_string(),
+		Opcode::Input => "in".to_string(),
+		Opcode::Output => "out".to_string(),
+		Opcode::JumpNonZero => "jnz".to_string(),
+		Opcode::JumpZero => "jz".to_string(),
+		Opcode::CompareLt => "lt".to_string(),
+		Opcode::CompareEq => "eq".to_string(),
+		Opcode::AdjustRelBase => "arb".to_string(),
+		Opcode::Halt => "hlt".to_string(),
+	}
+}
+
+/// Format a parameter: `[12]` for positions, `[rb+12]` for relative addresses and plain `12` for immediates.
+pub fn format_parameter(param: i128, mode: ParameterMode) -> String {
+	match mode {
+		ParameterMode::Position => format!("[{}]", param),
+		ParameterMode::Immediate => param.to_string(),
+		ParameterMode::Relative if param < 0 => format!("[rb{}]", param),
+		ParameterMode::Relative => format!("[rb+{}]", param),
+	}
+}
+
+impl fmt::Display for Instruction {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let params = self
+			.params
+			.iter()
+			.zip(self.modes.iter())
+			.map(|(param, mode)| format_parameter(*param, *mode))
+			.collect::<Vec<String>>();
+		if params.is_empty() {
+			write!(f, "{}", mnemonic(self.opcode))
+		} else {
+			write!(f, "{} {}", mnemonic(self.opcode), params.join(", "))
+		}
+	}
+}
+
+/// A line of disassembly.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Line {
+	Instruction(Instruction),
+	Data { address: usize, value: i128 },
+}
+
+impl Line {
+	pub fn address(&self) -> usize {
+		match self {
+			Line::Instruction(instruction) => instruction.address,
+			Line::Data { address, .. } => *address,
+		}
+	}
+}
+
+impl fmt::Display for Line {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Line::Instruction(instruction) => {
+				write!(f, "{:04}: {}", instruction.address, instruction)
+			}
+			Line::Data { address, value } => write!(f, "{:04}: data {}", address, value),
+		}
+	}
+}
+
+/// Disassemble a program by decoding it front to back.
+///
+/// Anything that doesn't decode as an instruction is treated as a data cell.
+/// Trailing zeros, like the extra memory [`crate::load_program`] allocates, are left out,
+/// unless they're the parameters of the last instruction.
+///
+/// ## Examples
+/// ```
+/// # use day09::disasm::disassemble;
+/// let program = [109, -1, 204, 1, 99, 7, 0, 0];
+/// let listing = disassemble(&program)
+/// 	.iter()
+/// 	.map(|line| line.to_string())
+/// 	.collect::<Vec<String>>();
+/// assert_eq!(listing, ["0000: arb -1", "0002: out [rb+1]", "0004: hlt", "0005: data 7"]);
+///
+/// let listing = disassemble(&[1101, 1, 2, 0, 0, 0]);
+/// assert_eq!(listing.len(), 1);
+/// assert_eq!(listing[0].to_string(), "0000: add 1, 2, [0]");
+/// ```
+pub fn disassemble(program: &[i128]) -> Vec<Line> {
+	// Decode the whole program, but don't start anything in the padding.
+	let end = program
+		.iter()
+		.rposition(|value| *value != 0)
+		.map_or(0, |last| last + 1);
+
+	let mut lines = Vec::new();
+	let mut address = 0;
+	while address < end {
+		match Instruction::decode(program, address) {
+			Some(instruction) => {
+				address += instruction.size();
+				lines.push(Line::Instruction(instruction));
+			}
+			None => {
+				lines.push(Line::Data {
+					address,
+					value: program[address],
+				});
+				address += 1;
+			}
+		}
+	}
+	lines
+}
+
+/// The addresses control flow can reach from address 0, without running the program.
+///
+/// Jumps are only followed when their target is an immediate, so code that's only reached through
+/// computed jumps is missing.
+///
+/// ## Examples
+/// ```
+/// # use day09::disasm::reachable;
+/// // Jump over the data cell at 3.
+/// let program = [1105, 1, 4, 7, 99];
+/// assert_eq!(reachable(&program).into_iter().collect::<Vec<_>>(), [0, 4]);
+/// ```
+pub fn reachable(program: &[i128]) -> BTreeSet<usize> {
+	let mut seen = BTreeSet::new();
+	let mut pending = vec![0];
+	while let Some(address) = pending.pop() {
+		if !seen.insert(address) {
+			continue;
+		}
+		let instruction = match Instruction::decode(program, address) {
+			Some(instruction) => instruction,
+			None => {
+				seen.remove(&address);
+				continue;
+			}
+		};
+		match instruction.opcode {
+			Opcode::Halt => (),
+			Opcode::JumpZero | Opcode::JumpNonZero => {
+				let (condition, target) = (instruction.params[0], instruction.params[1]);
+				// With an immediate condition, the jump always goes the same way.
+				let jumps = match instruction.modes[0] {
+					ParameterMode::Immediate => {
+						Some((condition == 0) == (instruction.opcode == Opcode::JumpZero))
+					}
+					_ => None,
+				};
+				if jumps != Some(false)
+					&& instruction.modes[1] == ParameterMode::Immediate
+					&& target >= 0
+				{
+					pending.push(target as usize);
+				}
+				if jumps != Some(true) {
+					pending.push(address + instruction.size());
+				}
+			}
+			_ => pending.push(address + instruction.size()),
+		}
+	}
+	seen
+}
@@ -0,0 +1,131 @@
+use std::io::{self, BufRead, Read, Write};
+
+/// Turns a source of values into the line-based input the VM reads.
+///
+/// The source returns `None` once it's out of values.
+///
+/// ## Examples
+/// ```
+/// # use day09::{execute_program, io::ValueReader};
+/// let mut values = vec![20, 22].into_iter();
+/// let reader = ValueReader::new(move || Ok(values.next()));
+///
+/// let mut program = [3, 9, 3, 10, 1, 9, 10, 11, 99, 0, 0, 0];
+/// execute_program(&mut program, reader, vec![]).unwrap();
+/// assert_eq!(program[11], 42);
+/// ```
+pub struct ValueReader<F> {
+	source: F,
+	buffer: Vec<u8>,
+	position: usize,
+}
+
+impl<F> ValueReader<F>
+where
+	F: FnMut() -> io::Result<Option<i128>>,
+{
+	pub fn new(source: F) -> Self {
+		Self {
+			source,
+			buffer: Vec::new(),
+			position: 0,
+		}
+	}
+}
+
+impl<F> Read for ValueReader<F>
+where
+	F: FnMut() -> io::Result<Option<i128>>,
+{
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let available = self.fill_buf()?;
+		let len = available.len().min(buf.len());
+		buf[..len].copy_from_slice(&available[..len]);
+		self.consume(len);
+		Ok(len)
+	}
+}
+
+impl<F> BufRead for ValueReader<F>
+where
+	F: FnMut() -> io::Result<Option<i128>>,
+{
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		if self.position >= self.buffer.len() {
+			self.buffer.clear();
+			self.position = 0;
+			if let Some(value) = (self.source)()? {
+				writeln!(&mut self.buffer, "{}", value)?;
+			}
+		}
+		Ok(&self.buffer[self.position..])
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.position = (self.position + amt).min(self.buffer.len());
+	}
+}
+
+/// Hands every value the VM outputs to a sink.
+///
+/// The VM writes each value as a line of text, which may arrive in several pieces.
+///
+/// ## Examples
+/// ```
+/// # use day09::{execute_program, io::ValueWriter};
+/// let mut values = Vec::new();
+/// let writer = ValueWriter::new(|value| {
+/// 	values.push(value);
+/// 	Ok(())
+/// });
+///
+/// let mut program = [104, -1, 104, 1125899906842624, 99];
+/// execute_program(&mut program, "".as_bytes(), writer).unwrap();
+/// assert_eq!(values, [-1, 1125899906842624]);
+/// ```
+pub struct ValueWriter<F> {
+	sink: F,
+	pending: Vec<u8>,
+}
+
+impl<F> ValueWriter<F>
+where
+	F: FnMut(i128) -> io::Result<()>,
+{
+	pub fn new(sink: F) -> Self {
+		Self {
+			sink,
+			pending: Vec::new(),
+		}
+	}
+}
+
+impl<F> Write for ValueWriter<F>
+where
+	F: FnMut(i128) -> io::Result<()>,
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		for byte in buf {
+			if *byte != b'\n' {
+				self.pending.push(*byte);
+				continue;
+			}
+
+			let line = String::from_utf8_lossy(&self.pending).trim().to_string();
+			self.pending.clear();
+			if line.is_empty() {
+				continue;
+			}
+			let value = line
+				.parse::<i128>()
+				.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+			(self.sink)(value)?;
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		// Values are handed on as soon as their line is complete.
+		Ok(())
+	}
+}
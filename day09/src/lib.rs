@@ -1,9 +1,27 @@
+// The repo indents with tabs, doc examples included.
+#![allow(clippy::tabs_in_doc_comments)]
+
 use std::error::Error;
 use std::fmt;
 use std::fs;
-use std::io::{BufRead, Write};
-
-#[derive(Debug)]
+use std::io::{BufRead, Read, Write};
+
+pub mod aot;
+pub mod asynchronous;
+pub mod coverage;
+pub mod disasm;
+pub mod io;
+pub mod loader;
+pub mod memory;
+pub mod network;
+pub mod registry;
+pub mod replay;
+pub mod threaded;
+
+use loader::LoadError;
+use registry::Registry;
+
+#[derive(Clone, Debug, Default)]
 pub struct State {
 	relative_base: i128,
 }
@@ -11,14 +29,17 @@ impl State {
 	pub fn new() -> Self {
 		Default::default()
 	}
-}
-impl Default for State {
-	fn default() -> Self {
-		Self { relative_base: 0 }
+
+	pub fn relative_base(&self) -> i128 {
+		self.relative_base
+	}
+
+	pub fn adjust_relative_base(&mut self, adjustment: i128) {
+		self.relative_base += adjustment;
 	}
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Opcode {
 	Add,
 	Mult,
@@ -74,6 +95,13 @@ pub enum IntcodeError {
 	InvalidAddress(i128),
 	TooFewParameterModes,
 	WrongParameterMode,
+	ReservedOpcode(u128),
+	/// The program wants input but there's none left.
+	EndOfInput,
+	InvalidInput(String),
+	/// The program wrote something that isn't a number.
+	InvalidOutput(String),
+	Io(std::io::Error),
 }
 
 impl fmt::Display for IntcodeError {
@@ -85,7 +113,16 @@ impl fmt::Display for IntcodeError {
 
 impl Error for IntcodeError {
 	fn source(&self) -> Option<&(dyn Error + 'static)> {
-		None
+		match self {
+			IntcodeError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<std::io::Error> for IntcodeError {
+	fn from(e: std::io::Error) -> Self {
+		IntcodeError::Io(e)
 	}
 }
 
@@ -107,14 +144,26 @@ impl ParameterMode {
 	}
 }
 
-pub fn load_program(file_path: &str, memory_size: usize) -> Result<Vec<i128>, std::io::Error> {
-	let file = fs::read_to_string(file_path)?;
-	let mut program = file
-		.trim()
-		.split(',')
-		.map(|s| s.parse::<i128>().unwrap())
-		.collect::<Vec<i128>>();
-	program.resize(memory_size, 0);
+/// Load a program from a file and extend it with zeroed memory up to `memory_size`.
+///
+/// See [`loader`] for the accepted formats.
+pub fn load_program(file_path: &str, memory_size: usize) -> Result<Vec<i128>, LoadError> {
+	load_program_from(fs::File::open(file_path)?, memory_size)
+}
+
+/// Load a program from any reader and extend it with zeroed memory up to `memory_size`.
+///
+/// ## Examples
+/// ```
+/// # use day09::load_program_from;
+/// let program = load_program_from("104,1125899906842624,99".as_bytes(), 5).unwrap();
+/// assert_eq!(program, [104, 1125899906842624, 99, 0, 0]);
+/// ```
+pub fn load_program_from<R: Read>(reader: R, memory_size: usize) -> Result<Vec<i128>, LoadError> {
+	let mut program = loader::load(reader)?;
+	if program.len() < memory_size {
+		program.resize(memory_size, 0);
+	}
 	Ok(program)
 }
 
@@ -217,6 +266,36 @@ where
 	}
 }
 
+/// Execute an Intcode program that may use the custom opcodes of a [`Registry`].
+///
+/// Built-in opcodes behave exactly as in [`execute_program`].
+pub fn execute_program_with<R, W>(
+	program: &mut [i128],
+	mut reader: R,
+	mut writer: W,
+	registry: &mut Registry<'_>,
+) -> Result<(), IntcodeError>
+where
+	R: BufRead,
+	W: Write,
+{
+	let mut idx: usize = 0;
+	let mut state = State::new();
+
+	loop {
+		if let Some(Opcode::Halt) = execute_step_with(
+			program,
+			&mut idx,
+			&mut state,
+			&mut reader,
+			&mut writer,
+			registry,
+		)? {
+			return Ok(());
+		};
+	}
+}
+
 pub fn execute_step<R, W>(
 	program: &mut [i128],
 	idx: &mut usize,
@@ -229,31 +308,52 @@ where
 	W: Write,
 {
 	let prev_idx = *idx;
-	let instruction = program[*idx];
+	let instruction = fetch(program, *idx)?;
 	if instruction < 0 {
 		return Err(IntcodeError::NegativeInstructionValue(instruction));
 	}
 
 	let (opcode, modes) = parse_instruction(instruction as u128)?;
 	match opcode {
-		Opcode::Add => add(program, *idx, &modes, &state)?,
-		Opcode::Mult => mult(program, *idx, &modes, &state)?,
-		Opcode::Input => input(program, *idx, &modes, reader, &state)?,
-		Opcode::Output => output(program, *idx, &modes, writer, &state)?,
+		Opcode::Add => add(program, *idx, &modes, state)?,
+		Opcode::Mult => mult(program, *idx, &modes, state)?,
+		Opcode::Input => input(program, *idx, &modes, reader, state)?,
+		Opcode::Output => output(program, *idx, &modes, writer, state)?,
 		Opcode::Halt => (),
-		Opcode::CompareEq => compare_eq(program, *idx, &modes, &state)?,
-		Opcode::CompareLt => compare_lt(program, *idx, &modes, &state)?,
-		Opcode::JumpZero => jump_zero(program, idx, &modes, &state)?,
-		Opcode::JumpNonZero => jump_non_zero(program, idx, &modes, &state)?,
+		Opcode::CompareEq => compare_eq(program, *idx, &modes, state)?,
+		Opcode::CompareLt => compare_lt(program, *idx, &modes, state)?,
+		Opcode::JumpZero => jump_zero(program, idx, &modes, state)?,
+		Opcode::JumpNonZero => jump_non_zero(program, idx, &modes, state)?,
 		Opcode::AdjustRelBase => adjust_relative_base(program, *idx, &modes, state)?,
 	}
 	if prev_idx == *idx {
 		// don't move our instruction pointer if we jumped
-		*idx += 1 + opcode.param_count() as usize;
+		*idx += 1 + opcode.param_count();
 	}
 	Ok(opcode)
 }
 
+/// Execute a single step, giving opcodes registered in `registry` precedence over unknown ones.
+///
+/// Returns the opcode of a built-in instruction, or `None` if it was a registered one.
+pub fn execute_step_with<R, W>(
+	program: &mut [i128],
+	idx: &mut usize,
+	state: &mut State,
+	reader: &mut R,
+	writer: &mut W,
+	registry: &mut Registry<'_>,
+) -> Result<Option<Opcode>, IntcodeError>
+where
+	R: BufRead,
+	W: Write,
+{
+	match registry.execute(program, idx, state)? {
+		Some(_) => Ok(None),
+		None => execute_step(program, idx, state, reader, writer).map(Some),
+	}
+}
+
 /// Parse an instruction into its opcode and its respective parameter modes.
 ///
 /// The last two digits are the opcode, the remaining are the parameter modes in reverse order.
@@ -266,10 +366,19 @@ where
 /// assert_eq!(modes, vec![ParameterMode::Position, ParameterMode::Immediate, ParameterMode::Position]);
 /// ```
 pub fn parse_instruction(instruction: u128) -> Result<(Opcode, Vec<ParameterMode>), IntcodeError> {
-	let (op_num, mut par_num) = (instruction % 100, instruction / 100);
+	let (op_num, par_num) = (instruction % 100, instruction / 100);
 	let op = Opcode::new(op_num)?;
-	let mut modes = Vec::with_capacity(op.param_count() as usize);
-	for _ in 0..op.param_count() {
+	let modes = parse_parameter_modes(par_num, op.param_count())?;
+
+	Ok((op, modes))
+}
+
+fn parse_parameter_modes(
+	mut par_num: u128,
+	count: usize,
+) -> Result<Vec<ParameterMode>, IntcodeError> {
+	let mut modes = Vec::with_capacity(count);
+	for _ in 0..count {
 		modes.push(ParameterMode::new(par_num % 10)?);
 		par_num /= 10;
 	}
@@ -278,7 +387,25 @@ pub fn parse_instruction(instruction: u128) -> Result<(Opcode, Vec<ParameterMode
 		return Err(IntcodeError::ExcessiveParameterModes(par_num));
 	}
 
-	Ok((op, modes))
+	Ok(modes)
+}
+
+/// The value at `address`, which has to be in memory.
+pub(crate) fn fetch(program: &[i128], address: usize) -> Result<i128, IntcodeError> {
+	program
+		.get(address)
+		.copied()
+		.ok_or(IntcodeError::InvalidAddress(address as i128))
+}
+
+fn store(program: &mut [i128], address: usize, value: i128) -> Result<(), IntcodeError> {
+	match program.get_mut(address) {
+		Some(cell) => {
+			*cell = value;
+			Ok(())
+		}
+		None => Err(IntcodeError::InvalidAddress(address as i128)),
+	}
 }
 
 fn parse_parameter(
@@ -291,7 +418,7 @@ fn parse_parameter(
 		Some(ParameterMode::Immediate) => Ok(param),
 		Some(ParameterMode::Position) | Some(ParameterMode::Relative) => {
 			match parse_address_parameter(param, mode, state) {
-				Ok(pos) => Ok(program[pos]),
+				Ok(pos) => fetch(program, pos),
 				Err(e) => Err(e),
 			}
 		}
@@ -358,13 +485,17 @@ pub fn add(
 	modes: &[ParameterMode],
 	state: &State,
 ) -> Result<(), IntcodeError> {
-	let (param_a, param_b, param_target) = (program[idx + 1], program[idx + 2], program[idx + 3]);
+	let (param_a, param_b, param_target) = (
+		fetch(program, idx + 1)?,
+		fetch(program, idx + 2)?,
+		fetch(program, idx + 3)?,
+	);
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
 	let b = parse_parameter(param_b, modes.next(), program, state)?;
 	let target = parse_address_parameter(param_target, modes.next(), state)?;
-	program[target] = a + b;
+	store(program, target, a + b)?;
 	Ok(())
 }
 
@@ -388,13 +519,17 @@ pub fn mult(
 	modes: &[ParameterMode],
 	state: &State,
 ) -> Result<(), IntcodeError> {
-	let (param_a, param_b, param_target) = (program[idx + 1], program[idx + 2], program[idx + 3]);
+	let (param_a, param_b, param_target) = (
+		fetch(program, idx + 1)?,
+		fetch(program, idx + 2)?,
+		fetch(program, idx + 3)?,
+	);
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
 	let b = parse_parameter(param_b, modes.next(), program, state)?;
 	let target = parse_address_parameter(param_target, modes.next(), state)?;
-	program[target] = a * b;
+	store(program, target, a * b)?;
 	Ok(())
 }
 
@@ -408,14 +543,26 @@ pub fn output<W>(
 where
 	W: Write,
 {
-	let param_a = program[idx + 1];
+	let param_a = fetch(program, idx + 1)?;
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
-	writeln!(&mut writer, "{}", a).expect("Can't write to output!");
+	writeln!(&mut writer, "{}", a)?;
 	Ok(())
 }
 
+/// Read a single value from `reader` and store it.
+///
+/// ## Examples
+/// ```
+/// # use day09::{execute_program, IntcodeError};
+/// let mut program = [3, 0, 99];
+/// let result = execute_program(&mut program, "".as_bytes(), vec![]);
+/// assert!(matches!(result, Err(IntcodeError::EndOfInput)));
+///
+/// let result = execute_program(&mut program, "seven\n".as_bytes(), vec![]);
+/// assert!(matches!(result, Err(IntcodeError::InvalidInput(_))));
+/// ```
 pub fn input<R>(
 	program: &mut [i128],
 	idx: usize,
@@ -426,15 +573,20 @@ pub fn input<R>(
 where
 	R: BufRead,
 {
-	let param_target = program[idx + 1];
+	let param_target = fetch(program, idx + 1)?;
 	let mut modes = modes.iter();
 
 	let target = parse_address_parameter(param_target, modes.next(), state)?;
 	let mut input = String::new();
-	reader.read_line(&mut input).unwrap();
-	let num = input.trim().parse::<i128>().unwrap();
+	if reader.read_line(&mut input)? == 0 {
+		return Err(IntcodeError::EndOfInput);
+	}
+	let num = input
+		.trim()
+		.parse::<i128>()
+		.map_err(|_| IntcodeError::InvalidInput(input.trim().to_string()))?;
 
-	program[target] = num;
+	store(program, target, num)?;
 	Ok(())
 }
 
@@ -444,13 +596,17 @@ pub fn compare_eq(
 	modes: &[ParameterMode],
 	state: &State,
 ) -> Result<(), IntcodeError> {
-	let (param_a, param_b, param_target) = (program[idx + 1], program[idx + 2], program[idx + 3]);
+	let (param_a, param_b, param_target) = (
+		fetch(program, idx + 1)?,
+		fetch(program, idx + 2)?,
+		fetch(program, idx + 3)?,
+	);
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
 	let b = parse_parameter(param_b, modes.next(), program, state)?;
 	let target = parse_address_parameter(param_target, modes.next(), state)?;
-	program[target] = if a == b { 1 } else { 0 };
+	store(program, target, if a == b { 1 } else { 0 })?;
 	Ok(())
 }
 
@@ -460,13 +616,17 @@ pub fn compare_lt(
 	modes: &[ParameterMode],
 	state: &State,
 ) -> Result<(), IntcodeError> {
-	let (param_a, param_b, param_target) = (program[idx + 1], program[idx + 2], program[idx + 3]);
+	let (param_a, param_b, param_target) = (
+		fetch(program, idx + 1)?,
+		fetch(program, idx + 2)?,
+		fetch(program, idx + 3)?,
+	);
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
 	let b = parse_parameter(param_b, modes.next(), program, state)?;
 	let target = parse_address_parameter(param_target, modes.next(), state)?;
-	program[target] = if a < b { 1 } else { 0 };
+	store(program, target, if a < b { 1 } else { 0 })?;
 	Ok(())
 }
 
@@ -476,7 +636,7 @@ pub fn jump_zero(
 	modes: &[ParameterMode],
 	state: &State,
 ) -> Result<(), IntcodeError> {
-	let (param_a, param_target) = (program[*idx + 1], program[*idx + 2]);
+	let (param_a, param_target) = (fetch(program, *idx + 1)?, fetch(program, *idx + 2)?);
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
@@ -493,7 +653,7 @@ pub fn jump_non_zero(
 	modes: &[ParameterMode],
 	state: &State,
 ) -> Result<(), IntcodeError> {
-	let (param_a, param_target) = (program[*idx + 1], program[*idx + 2]);
+	let (param_a, param_target) = (fetch(program, *idx + 1)?, fetch(program, *idx + 2)?);
 	let mut modes = modes.iter();
 
 	let a = parse_parameter(param_a, modes.next(), program, state)?;
@@ -510,11 +670,11 @@ fn adjust_relative_base(
 	modes: &[ParameterMode],
 	state: &mut State,
 ) -> Result<(), IntcodeError> {
-	let param = program[idx + 1];
+	let param = fetch(program, idx + 1)?;
 	let mut modes = modes.iter();
 
 	let adjustment = parse_parameter(param, modes.next(), program, state)?;
-	state.relative_base += adjustment;
+	state.adjust_relative_base(adjustment);
 	Ok(())
 }
 
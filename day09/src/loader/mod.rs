@@ -0,0 +1,257 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+
+/// How a program image is stored.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+	/// Values separated by commas, whitespace or newlines.
+	///
+	/// `#` starts a comment that runs until the end of the line.
+	/// A line may start with an `address:` label, as found in memory dumps,
+	/// which places the following values at that address.
+	Text,
+	/// Little-endian `i64` values without any separators.
+	Binary,
+}
+
+impl Format {
+	/// Guess the format of a program image.
+	///
+	/// Anything that isn't printable ASCII text is considered binary.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::loader::Format;
+	/// assert_eq!(Format::detect(b"1,0,0,0,99\n"), Format::Text);
+	/// assert_eq!(Format::detect(&99i64.to_le_bytes()), Format::Binary);
+	/// ```
+	pub fn detect(bytes: &[u8]) -> Self {
+		if bytes
+			.iter()
+			.all(|b| b.is_ascii_graphic() || b.is_ascii_whitespace())
+		{
+			Format::Text
+		} else {
+			Format::Binary
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+	Io(io::Error),
+	/// A token that isn't a valid value. Lines and columns start at 1.
+	InvalidValue {
+		line: usize,
+		column: usize,
+		token: String,
+	},
+	/// An address label that points to memory we've already filled.
+	OverlappingAddress {
+		line: usize,
+		column: usize,
+		address: usize,
+	},
+	/// Binary images consist of 8 byte values, this one has a different length.
+	TruncatedBinary(usize),
+}
+
+impl fmt::Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LoadError::Io(e) => write!(f, "Can't read program: {}", e),
+			LoadError::InvalidValue {
+				line,
+				column,
+				token,
+			} => write!(f, "{}:{}: invalid value `{}`", line, column, token),
+			LoadError::OverlappingAddress {
+				line,
+				column,
+				address,
+			} => write!(
+				f,
+				"{}:{}: address {} overlaps earlier values",
+				line, column, address
+			),
+			LoadError::TruncatedBinary(len) => write!(
+				f,
+				"binary program of {} bytes isn't a multiple of 8 bytes long",
+				len
+			),
+		}
+	}
+}
+
+impl Error for LoadError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			LoadError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for LoadError {
+	fn from(e: io::Error) -> Self {
+		LoadError::Io(e)
+	}
+}
+
+/// Load a program from any reader, detecting its format.
+///
+/// ## Examples
+/// ```
+/// # use day09::loader::load;
+/// let program = load("1,0,0,0,99\n".as_bytes()).unwrap();
+/// assert_eq!(program, [1, 0, 0, 0, 99]);
+///
+/// let binary = [1i64, 0, 0, 0, 99]
+/// 	.iter()
+/// 	.flat_map(|n| n.to_le_bytes().to_vec())
+/// 	.collect::<Vec<u8>>();
+/// assert_eq!(load(&binary[..]).unwrap(), [1, 0, 0, 0, 99]);
+/// ```
+pub fn load<R: Read>(mut reader: R) -> Result<Vec<i128>, LoadError> {
+	let mut bytes = Vec::new();
+	reader.read_to_end(&mut bytes)?;
+	parse(&bytes, Format::detect(&bytes))
+}
+
+/// Parse a program image of a known format.
+pub fn parse(bytes: &[u8], format: Format) -> Result<Vec<i128>, LoadError> {
+	match format {
+		Format::Text => parse_text(&String::from_utf8_lossy(bytes)),
+		Format::Binary => parse_binary(bytes),
+	}
+}
+
+/// Parse the text form of a program.
+///
+/// ## Examples
+/// Whitespace, newlines and comments.
+/// ```
+/// # use day09::loader::parse_text;
+/// let text = "# Add, then halt\n1, 0, 0, 0\n\n  99 # done\n";
+/// assert_eq!(parse_text(text).unwrap(), [1, 0, 0, 0, 99]);
+/// ```
+///
+/// Address labels fill gaps with zeros.
+/// ```
+/// # use day09::loader::parse_text;
+/// let text = "0000: 1 5 6 7\n0004: 99\n0007: 3";
+/// assert_eq!(parse_text(text).unwrap(), [1, 5, 6, 7, 99, 0, 0, 3]);
+/// ```
+///
+/// Errors know where they happened.
+/// ```
+/// # use day09::loader::{parse_text, LoadError};
+/// match parse_text("1,0,0,0\n99,O") {
+/// 	Err(LoadError::InvalidValue { line, column, token }) => {
+/// 		assert_eq!((line, column, token.as_str()), (2, 4, "O"));
+/// 	}
+/// 	_ => panic!("Expected an invalid value"),
+/// }
+/// ```
+pub fn parse_text(text: &str) -> Result<Vec<i128>, LoadError> {
+	let mut program = Vec::new();
+
+	for (line_idx, line) in text.lines().enumerate() {
+		let line_number = line_idx + 1;
+		let code = match line.find('#') {
+			Some(comment) => &line[..comment],
+			None => line,
+		};
+
+		for (token_idx, (column, token)) in tokenize(code).into_iter().enumerate() {
+			let value = match (token_idx, token.find(':')) {
+				(0, Some(colon)) => {
+					let label = &token[..colon];
+					let address = label
+						.parse::<usize>()
+						.map_err(|_| LoadError::InvalidValue {
+							line: line_number,
+							column,
+							token: token.to_string(),
+						})?;
+					if address < program.len() {
+						return Err(LoadError::OverlappingAddress {
+							line: line_number,
+							column,
+							address,
+						});
+					}
+					program.resize(address, 0);
+
+					// The first value may follow the label without a space.
+					match &token[colon + 1..] {
+						"" => continue,
+						rest => (column + colon + 1, rest),
+					}
+				}
+				_ => (column, token),
+			};
+			program.push(parse_value(line_number, value.0, value.1)?);
+		}
+	}
+
+	Ok(program)
+}
+
+/// Parse a program stored as little-endian `i64` values.
+///
+/// ## Examples
+/// ```
+/// # use day09::loader::{parse_binary, LoadError};
+/// assert_eq!(parse_binary(&(-1i64).to_le_bytes()).unwrap(), [-1]);
+/// assert!(matches!(parse_binary(&[0; 7]), Err(LoadError::TruncatedBinary(7))));
+/// ```
+pub fn parse_binary(bytes: &[u8]) -> Result<Vec<i128>, LoadError> {
+	if bytes.len() % 8 != 0 {
+		return Err(LoadError::TruncatedBinary(bytes.len()));
+	}
+
+	Ok(bytes
+		.chunks_exact(8)
+		.map(|chunk| {
+			let mut value = [0; 8];
+			value.copy_from_slice(chunk);
+			i64::from_le_bytes(value) as i128
+		})
+		.collect())
+}
+
+/// Split a line into its tokens along with their 1-based columns.
+fn tokenize(line: &str) -> Vec<(usize, &str)> {
+	let mut tokens = Vec::new();
+	let mut start = None;
+
+	for (column, (idx, c)) in line.char_indices().enumerate() {
+		match (start, is_separator(c)) {
+			(None, false) => start = Some((column + 1, idx)),
+			(Some((token_column, token_start)), true) => {
+				tokens.push((token_column, &line[token_start..idx]));
+				start = None;
+			}
+			_ => (),
+		}
+	}
+	if let Some((token_column, token_start)) = start {
+		tokens.push((token_column, &line[token_start..]));
+	}
+
+	tokens
+}
+
+fn is_separator(c: char) -> bool {
+	c == ',' || c.is_whitespace()
+}
+
+fn parse_value(line: usize, column: usize, token: &str) -> Result<i128, LoadError> {
+	token.parse::<i128>().map_err(|_| LoadError::InvalidValue {
+		line,
+		column,
+		token: token.to_string(),
+	})
+}
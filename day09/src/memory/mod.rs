@@ -0,0 +1,320 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use std::str::FromStr;
+
+use crate::IntcodeError;
+
+#[derive(Debug)]
+pub enum PatchError {
+	Io(io::Error),
+	/// Something that isn't an `address=value` pair.
+	InvalidAssignment(String),
+	/// A line of a patch file that isn't an `address=value` pair. Lines start at 1.
+	InvalidLine { line: usize, text: String },
+}
+
+impl fmt::Display for PatchError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PatchError::Io(e) => write!(f, "Can't read patch: {}", e),
+			PatchError::InvalidAssignment(text) => {
+				write!(f, "`{}` isn't of the form address=value", text)
+			}
+			PatchError::InvalidLine { line, text } => write!(
+				f,
+				"Line {} of the patch file, `{}`, isn't of the form address=value",
+				line, text
+			),
+		}
+	}
+}
+
+impl Error for PatchError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			PatchError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for PatchError {
+	fn from(e: io::Error) -> Self {
+		PatchError::Io(e)
+	}
+}
+
+/// Memory cells to overwrite before running a program.
+///
+/// ## Examples
+/// The "1202 program alarm" state, see [`crate::restore_to_alarm_state`].
+/// ```
+/// # use day09::memory::Patch;
+/// let patch = "1=12, 2=2".parse::<Patch>().unwrap();
+/// let mut program = [1, 0, 0, 3, 99];
+/// patch.apply(&mut program).unwrap();
+/// assert_eq!(program, [1, 12, 2, 3, 99]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Patch {
+	cells: Vec<(usize, i128)>,
+}
+
+impl Patch {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Overwrite `address` with `value`. Later assignments win.
+	pub fn set(&mut self, address: usize, value: i128) {
+		self.cells.push((address, value));
+	}
+
+	pub fn cells(&self) -> &[(usize, i128)] {
+		&self.cells
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.cells.is_empty()
+	}
+
+	/// Read a patch file: one `address=value` pair per line, `#` starts a comment.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::memory::{Patch, PatchError};
+	/// let patch = Patch::load("# insert coins\n0=2\n".as_bytes()).unwrap();
+	/// assert_eq!(patch.cells(), &[(0, 2)]);
+	/// assert!(matches!(
+	/// 	Patch::load("0=2\n0:2\n".as_bytes()),
+	/// 	Err(PatchError::InvalidLine { line: 2, .. })
+	/// ));
+	/// ```
+	pub fn load<R: Read>(reader: R) -> Result<Self, PatchError> {
+		let mut patch = Patch::new();
+		for (line_idx, line) in io::BufReader::new(reader).lines().enumerate() {
+			let line = line?;
+			let assignment = match line.find('#') {
+				Some(comment) => &line[..comment],
+				None => &line[..],
+			};
+			if assignment.trim().is_empty() {
+				continue;
+			}
+			let (address, value) =
+				parse_assignment(assignment).map_err(|_| PatchError::InvalidLine {
+					line: line_idx + 1,
+					text: line.trim().to_string(),
+				})?;
+			patch.set(address, value);
+		}
+		Ok(patch)
+	}
+
+	/// Add all of another patch's assignments after ours.
+	pub fn extend(&mut self, other: &Patch) {
+		self.cells.extend_from_slice(&other.cells);
+	}
+
+	/// Write our values to memory, failing without changes if an address is out of bounds.
+	pub fn apply(&self, program: &mut [i128]) -> Result<(), IntcodeError> {
+		if let Some((address, _)) = self
+			.cells
+			.iter()
+			.find(|(address, _)| *address >= program.len())
+		{
+			return Err(IntcodeError::InvalidAddress(*address as i128));
+		}
+		for (address, value) in self.cells.iter() {
+			program[*address] = *value;
+		}
+		Ok(())
+	}
+}
+
+/// Parse a single `address=value` pair.
+///
+/// ## Examples
+/// ```
+/// # use day09::memory::parse_assignment;
+/// assert_eq!(parse_assignment("0=2").unwrap(), (0, 2));
+/// assert_eq!(parse_assignment(" 12 = -1 ").unwrap(), (12, -1));
+/// assert!(parse_assignment("0:2").is_err());
+/// ```
+pub fn parse_assignment(text: &str) -> Result<(usize, i128), PatchError> {
+	let invalid = || PatchError::InvalidAssignment(text.trim().to_string());
+	let mut parts = text.splitn(2, '=');
+	match (parts.next(), parts.next()) {
+		(Some(address), Some(value)) => Ok((
+			address.trim().parse().map_err(|_| invalid())?,
+			value.trim().parse().map_err(|_| invalid())?,
+		)),
+		_ => Err(invalid()),
+	}
+}
+
+impl FromStr for Patch {
+	type Err = PatchError;
+
+	/// Parse `address=value` pairs separated by commas or whitespace.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut patch = Patch::new();
+		for assignment in s
+			.split(|c: char| c == ',' || c.is_whitespace())
+			.filter(|part| !part.is_empty())
+		{
+			let (address, value) = parse_assignment(assignment)?;
+			patch.set(address, value);
+		}
+		Ok(patch)
+	}
+}
+
+/// A memory image formatted as a table, one row of cells per line, each starting with its address.
+///
+/// Rows that only hold zeros are left out. The output can be read back with [`crate::loader`],
+/// which fills the gaps with zeros again.
+///
+/// ## Examples
+/// ```
+/// # use day09::memory::Dump;
+/// let mut memory = vec![1, 0, 0, 3, 99];
+/// memory.resize(20, 0);
+/// memory[17] = -1;
+/// assert_eq!(
+/// 	Dump::new(&memory).width(4).to_string(),
+/// 	"0000:  1  0  0  3\n0004: 99  0  0  0\n0016:  0 -1  0  0\n"
+/// );
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Dump<'a> {
+	memory: &'a [i128],
+	width: usize,
+}
+
+impl<'a> Dump<'a> {
+	pub fn new(memory: &'a [i128]) -> Self {
+		Self { memory, width: 8 }
+	}
+
+	/// Set the number of cells per row. Defaults to 8.
+	pub fn width(mut self, width: usize) -> Self {
+		self.width = width.max(1);
+		self
+	}
+}
+
+impl fmt::Display for Dump<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let value_width = self
+			.memory
+			.iter()
+			.map(|value| value.to_string().len())
+			.max()
+			.unwrap_or(1);
+		for (row_idx, row) in self.memory.chunks(self.width).enumerate() {
+			if row.iter().all(|value| *value == 0) {
+				continue;
+			}
+			write!(f, "{:04}:", row_idx * self.width)?;
+			for value in row {
+				write!(f, " {:>width$}", value, width = value_width)?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+/// Consecutive memory cells that differ between two images.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Run {
+	pub address: usize,
+	pub before: Vec<i128>,
+	pub after: Vec<i128>,
+}
+
+impl Run {
+	pub fn len(&self) -> usize {
+		self.after.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.after.is_empty()
+	}
+}
+
+impl fmt::Display for Run {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let join = |values: &[i128]| {
+			values
+				.iter()
+				.map(|value| value.to_string())
+				.collect::<Vec<String>>()
+				.join(" ")
+		};
+		if self.len() == 1 {
+			write!(f, "{:04}: ", self.address)?;
+		} else {
+			write!(
+				f,
+				"{:04}..{:04}: ",
+				self.address,
+				self.address + self.len() - 1
+			)?;
+		}
+		write!(f, "{} -> {}", join(&self.before), join(&self.after))
+	}
+}
+
+/// Find the cells that differ between two memory images.
+///
+/// Cells past the end of the shorter image count as zero, like unused memory does.
+///
+/// ## Examples
+/// ```
+/// # use day09::memory::diff;
+/// let before = [1, 0, 0, 3, 99];
+/// let after = [1, 12, 2, 3, 99, 0, 7];
+/// let runs = diff(&before, &after);
+/// assert_eq!(runs.len(), 2);
+/// assert_eq!(runs[0].to_string(), "0001..0002: 0 0 -> 12 2");
+/// assert_eq!(runs[1].to_string(), "0006: 0 -> 7");
+/// ```
+pub fn diff(before: &[i128], after: &[i128]) -> Vec<Run> {
+	let cell = |memory: &[i128], address: usize| memory.get(address).copied().unwrap_or(0);
+
+	let mut runs: Vec<Run> = Vec::new();
+	for address in 0..before.len().max(after.len()) {
+		let (old, new) = (cell(before, address), cell(after, address));
+		if old == new {
+			continue;
+		}
+		match runs.last_mut() {
+			Some(run) if run.address + run.len() == address => {
+				run.before.push(old);
+				run.after.push(new);
+			}
+			_ => runs.push(Run {
+				address,
+				before: vec![old],
+				after: vec![new],
+			}),
+		}
+	}
+	runs
+}
+
+impl From<&[Run]> for Patch {
+	/// The patch that turns the `before` side of a diff into the `after` side.
+	fn from(runs: &[Run]) -> Self {
+		let mut patch = Patch::new();
+		for run in runs {
+			for (offset, value) in run.after.iter().enumerate() {
+				patch.set(run.address + offset, *value);
+			}
+		}
+		patch
+	}
+}
@@ -0,0 +1,281 @@
+use std::cell::Cell;
+use std::collections::{HashSet, VecDeque};
+
+use crate::io::{ValueReader, ValueWriter};
+use crate::{execute_step, IntcodeError, Opcode, State};
+
+/// A message from one machine to another.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Packet {
+	pub dest: i128,
+	pub x: i128,
+	pub y: i128,
+}
+
+/// Something the host of a [`Network`] may want to react to.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Event {
+	/// A packet was sent to a watched address.
+	Monitored(Packet),
+	/// A packet was sent to an address that's neither a machine nor watched.
+	Undeliverable(Packet),
+	/// No machine has anything to do until someone sends it a packet.
+	Idle,
+	/// Every machine has halted.
+	Halted,
+}
+
+/// Machines that read this many times in a row from an empty queue, without any other input or
+/// output in between, are idle.
+const IDLE_READS: u32 = 2;
+
+/// Instructions a machine runs at most in one turn, so one that never does I/O can't hold up the
+/// others.
+const TURN_LENGTH: usize = 10_000;
+
+struct Node {
+	program: Vec<i128>,
+	idx: usize,
+	state: State,
+	inbox: VecDeque<i128>,
+	outbox: Vec<i128>,
+	halted: bool,
+	empty_reads: u32,
+}
+
+/// The opcode of the instruction at `idx`, without its parameter modes.
+fn next_code(program: &[i128], idx: usize) -> Option<i128> {
+	program.get(idx).map(|instruction| instruction % 100)
+}
+
+impl Node {
+	/// Halted, or about to read from an empty queue yet again.
+	fn is_idle(&self) -> bool {
+		self.halted
+			|| (self.inbox.is_empty()
+				&& self.outbox.is_empty()
+				&& self.empty_reads >= IDLE_READS
+				&& next_code(&self.program, self.idx) == Some(3))
+	}
+}
+
+/// Many machines running cooperatively on a single thread, exchanging packets.
+///
+/// Every machine receives its address as its first input. After that, each output triple
+/// `dest, x, y` is delivered to machine `dest` as the two inputs `x, y`. A machine
+/// reading from an empty queue receives `-1` instead of waiting.
+///
+/// ## Examples
+/// Machines that forward everything they receive to address 255.
+/// ```
+/// # use day09::network::{Event, Network, Packet};
+/// let mut program = vec![
+/// 	3, 50, 3, 51, 1008, 51, -1, 52, 1005, 52, 2, 3, 53, 104, 255, 4, 51, 4, 53, 1105, 1, 2,
+/// ];
+/// program.resize(54, 0);
+///
+/// let mut network = Network::new(&program, 3);
+/// network.watch(255);
+/// assert_eq!(network.run().unwrap(), Event::Idle);
+///
+/// network.send(Packet { dest: 1, x: 5, y: 6 });
+/// assert_eq!(network.run().unwrap(), Event::Monitored(Packet { dest: 255, x: 5, y: 6 }));
+/// assert_eq!(network.run().unwrap(), Event::Idle);
+/// ```
+pub struct Network {
+	nodes: Vec<Node>,
+	watched: HashSet<i128>,
+	events: VecDeque<Event>,
+}
+
+impl Network {
+	/// Boot `count` copies of a program with the addresses `0..count`.
+	pub fn new(program: &[i128], count: usize) -> Self {
+		let nodes = (0..count)
+			.map(|address| Node {
+				program: program.to_vec(),
+				idx: 0,
+				state: State::new(),
+				inbox: vec![address as i128].into_iter().collect(),
+				outbox: Vec::with_capacity(3),
+				halted: false,
+				empty_reads: 0,
+			})
+			.collect();
+		Self {
+			nodes,
+			watched: HashSet::new(),
+			events: VecDeque::new(),
+		}
+	}
+
+	/// Report packets sent to `address` as [`Event::Monitored`] instead of delivering them.
+	pub fn watch(&mut self, address: i128) {
+		self.watched.insert(address);
+	}
+
+	/// Deliver a packet from outside the network.
+	pub fn send(&mut self, packet: Packet) {
+		self.route(packet);
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	/// Whether no machine can make progress without outside help.
+	///
+	/// ## Examples
+	/// A machine that finds its queue empty twice, then thinks for a while before it sends.
+	/// ```
+	/// # use day09::network::{Event, Network, Packet};
+	/// let mut program = vec![3, 100, 3, 100, 3, 100];
+	/// for _ in 0..10 {
+	/// 	program.extend_from_slice(&[1101, 1, 1, 101]);
+	/// }
+	/// program.extend_from_slice(&[104, 255, 104, 1, 104, 2, 99]);
+	/// program.resize(102, 0);
+	///
+	/// let mut network = Network::new(&program, 1);
+	/// network.watch(255);
+	/// assert_eq!(network.run().unwrap(), Event::Monitored(Packet { dest: 255, x: 1, y: 2 }));
+	/// ```
+	pub fn is_idle(&self) -> bool {
+		self.nodes.iter().all(Node::is_idle)
+	}
+
+	/// Memory of the machine at `address`.
+	pub fn memory(&self, address: usize) -> Option<&[i128]> {
+		self.nodes.get(address).map(|node| &node.program[..])
+	}
+
+	/// Run the network until there's an event for the host.
+	///
+	/// [`Event::Idle`] is reported every time the network settles down,
+	/// which is the host's chance to wake it up with a packet.
+	///
+	/// ## Examples
+	/// Machine 0 never stops computing, machine 1 gets to send all the same.
+	/// ```
+	/// # use day09::network::{Event, Network, Packet};
+	/// let mut program = vec![3, 100, 1005, 100, 8, 1105, 1, 5, 104, 255, 104, 1, 104, 2, 99];
+	/// program.resize(101, 0);
+	///
+	/// let mut network = Network::new(&program, 2);
+	/// network.watch(255);
+	/// assert_eq!(network.run().unwrap(), Event::Monitored(Packet { dest: 255, x: 1, y: 2 }));
+	/// ```
+	pub fn run(&mut self) -> Result<Event, IntcodeError> {
+		loop {
+			if let Some(event) = self.events.pop_front() {
+				return Ok(event);
+			}
+			if self.nodes.iter().all(|node| node.halted) {
+				return Ok(Event::Halted);
+			}
+			if self.is_idle() {
+				for node in self.nodes.iter_mut() {
+					node.empty_reads = 0;
+				}
+				return Ok(Event::Idle);
+			}
+			self.round()?;
+		}
+	}
+
+	/// Let every machine run through its next input or output, and on up to the one after, or for
+	/// [`TURN_LENGTH`] instructions if that comes first.
+	///
+	/// Between rounds every machine is about to do I/O or still computing, so one that's busy
+	/// computing is never taken for idle.
+	fn round(&mut self) -> Result<(), IntcodeError> {
+		for address in 0..self.nodes.len() {
+			if let Some(packet) = self.step(address)? {
+				self.route(packet);
+			}
+		}
+		Ok(())
+	}
+
+	fn step(&mut self, address: usize) -> Result<Option<Packet>, IntcodeError> {
+		let Node {
+			program,
+			idx,
+			state,
+			inbox,
+			outbox,
+			halted,
+			empty_reads,
+		} = &mut self.nodes[address];
+		if *halted {
+			return Ok(None);
+		}
+
+		let read_empty = Cell::new(false);
+		let mut reader = ValueReader::new(|| match inbox.pop_front() {
+			Some(value) => Ok(Some(value)),
+			None => {
+				read_empty.set(true);
+				Ok(Some(-1))
+			}
+		});
+		let mut writer = ValueWriter::new(|value| {
+			outbox.push(value);
+			Ok(())
+		});
+
+		let mut done_io = false;
+		for _ in 0..TURN_LENGTH {
+			if done_io && matches!(next_code(program, *idx), Some(3 | 4 | 99)) {
+				break;
+			}
+			match execute_step(program, idx, state, &mut reader, &mut writer)? {
+				Opcode::Halt => {
+					*halted = true;
+					break;
+				}
+				Opcode::Input if read_empty.replace(false) => *empty_reads += 1,
+				Opcode::Input | Opcode::Output => *empty_reads = 0,
+				_ => continue,
+			}
+			done_io = true;
+		}
+		drop(reader);
+		drop(writer);
+
+		if outbox.len() < 3 {
+			return Ok(None);
+		}
+		let packet = Packet {
+			dest: outbox[0],
+			x: outbox[1],
+			y: outbox[2],
+		};
+		outbox.clear();
+		Ok(Some(packet))
+	}
+
+	fn route(&mut self, packet: Packet) {
+		if self.watched.contains(&packet.dest) {
+			self.events.push_back(Event::Monitored(packet));
+			return;
+		}
+		let node = if packet.dest < 0 {
+			None
+		} else {
+			self.nodes.get_mut(packet.dest as usize)
+		};
+		match node {
+			Some(node) => {
+				node.inbox.push_back(packet.x);
+				node.inbox.push_back(packet.y);
+				node.empty_reads = 0;
+			}
+			None => self.events.push_back(Event::Undeliverable(packet)),
+		}
+	}
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{
+	fetch, parse_address_parameter, parse_parameter_modes, IntcodeError, Opcode, ParameterMode,
+	State,
+};
+
+/// A parameter of a custom instruction, resolved according to its parameter mode.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Parameter {
+	/// The parameter exactly as it appears in the program.
+	pub raw: i128,
+	pub mode: ParameterMode,
+	/// The parameter itself in immediate mode, otherwise the value stored at its address.
+	pub value: i128,
+	/// The address the parameter points to, unless it's in immediate mode.
+	pub address: Option<usize>,
+}
+
+/// Called with the resolved parameters, the program's memory and the machine state.
+///
+/// Returning `Some(address)` jumps to that address, `None` continues with the next instruction.
+pub type Handler<'a> =
+	dyn FnMut(&[Parameter], &mut [i128], &mut State) -> Result<Option<usize>, IntcodeError> + 'a;
+
+struct CustomOpcode<'a> {
+	param_count: usize,
+	handler: Box<Handler<'a>>,
+}
+
+/// Host-defined opcodes that extend the built-in instruction set.
+///
+/// Built-in opcodes can't be overridden, so an empty registry behaves exactly like the plain VM.
+///
+/// ## Examples
+/// ```
+/// # use day09::{execute_program_with, registry::Registry};
+/// let mut seen = Vec::new();
+/// let mut registry = Registry::new();
+/// registry
+/// 	.register(42, 1, |params, _, _| {
+/// 		seen.push(params[0].value);
+/// 		Ok(None)
+/// 	})
+/// 	.unwrap();
+///
+/// let mut program = [142, 7, 42, 0, 99];
+/// let (input, output) = ("".as_bytes(), vec![]);
+/// execute_program_with(&mut program, input, output, &mut registry).unwrap();
+/// drop(registry);
+/// assert_eq!(seen, [7, 142]);
+/// ```
+#[derive(Default)]
+pub struct Registry<'a> {
+	opcodes: HashMap<u128, CustomOpcode<'a>>,
+}
+
+impl<'a> Registry<'a> {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Register a custom opcode with a fixed number of parameters.
+	///
+	/// Fails for the opcodes of the built-in instruction set and for opcodes that don't fit in two digits.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day09::{registry::Registry, IntcodeError};
+	/// let mut registry = Registry::new();
+	/// assert!(registry.register(50, 0, |_, _, _| Ok(None)).is_ok());
+	/// assert!(matches!(
+	/// 	registry.register(1, 3, |_, _, _| Ok(None)),
+	/// 	Err(IntcodeError::ReservedOpcode(1))
+	/// ));
+	/// assert!(registry.register(100, 0, |_, _, _| Ok(None)).is_err());
+	/// ```
+	pub fn register<F>(
+		&mut self,
+		code: u128,
+		param_count: usize,
+		handler: F,
+	) -> Result<(), IntcodeError>
+	where
+		F: FnMut(&[Parameter], &mut [i128], &mut State) -> Result<Option<usize>, IntcodeError> + 'a,
+	{
+		if code >= 100 || Opcode::new(code).is_ok() {
+			return Err(IntcodeError::ReservedOpcode(code));
+		}
+		self.opcodes.insert(
+			code,
+			CustomOpcode {
+				param_count,
+				handler: Box::new(handler),
+			},
+		);
+		Ok(())
+	}
+
+	pub fn unregister(&mut self, code: u128) -> bool {
+		self.opcodes.remove(&code).is_some()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.opcodes.is_empty()
+	}
+
+	/// Execute the instruction at `idx` if it's one of ours, and return its opcode.
+	///
+	/// Returns `None` without touching anything if the instruction isn't a registered opcode.
+	///
+	/// ## Examples
+	/// Jumping over the rest of the program.
+	/// ```
+	/// # use day09::{registry::Registry, State};
+	/// let mut registry = Registry::new();
+	/// registry.register(77, 1, |params, _, _| Ok(Some(params[0].value as usize))).unwrap();
+	///
+	/// let mut program = [177, 5, 1, 0, 0, 99];
+	/// let (mut idx, mut state) = (0, State::new());
+	/// let op = registry.execute(&mut program, &mut idx, &mut state).unwrap();
+	/// assert_eq!(op, Some(77));
+	/// assert_eq!(idx, 5);
+	/// assert_eq!(registry.execute(&mut program, &mut idx, &mut state).unwrap(), None);
+	/// ```
+	pub fn execute(
+		&mut self,
+		program: &mut [i128],
+		idx: &mut usize,
+		state: &mut State,
+	) -> Result<Option<u128>, IntcodeError> {
+		if self.opcodes.is_empty() {
+			return Ok(None);
+		}
+		let instruction = fetch(program, *idx)?;
+		if instruction < 0 {
+			return Ok(None);
+		}
+
+		let (code, par_num) = (instruction as u128 % 100, instruction as u128 / 100);
+		let custom = match self.opcodes.get_mut(&code) {
+			Some(custom) => custom,
+			None => return Ok(None),
+		};
+
+		let modes = parse_parameter_modes(par_num, custom.param_count)?;
+		let params = modes
+			.iter()
+			.enumerate()
+			.map(|(offset, mode)| {
+				resolve(fetch(program, *idx + 1 + offset)?, *mode, program, state)
+			})
+			.collect::<Result<Vec<Parameter>, IntcodeError>>()?;
+
+		match (custom.handler)(&params, program, state)? {
+			Some(target) => *idx = target,
+			None => *idx += 1 + custom.param_count,
+		}
+		Ok(Some(code))
+	}
+}
+
+fn resolve(
+	raw: i128,
+	mode: ParameterMode,
+	program: &[i128],
+	state: &State,
+) -> Result<Parameter, IntcodeError> {
+	let (value, address) = match mode {
+		ParameterMode::Immediate => (raw, None),
+		_ => {
+			let address = parse_address_parameter(raw, Some(&mode), state)?;
+			let value = fetch(program, address)?;
+			(value, Some(address))
+		}
+	};
+	Ok(Parameter {
+		raw,
+		mode,
+		value,
+		address,
+	})
+}
+
+impl fmt::Debug for Registry<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut codes = self.opcodes.keys().collect::<Vec<_>>();
+		codes.sort();
+		f.debug_struct("Registry").field("opcodes", &codes).finish()
+	}
+}
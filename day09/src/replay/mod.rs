@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+use crate::{execute_step, IntcodeError, Opcode, State};
+
+/// A value the program read and the step at which it asked for it.
+///
+/// Steps count the instructions executed before, starting at 0.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+	pub step: u64,
+	pub value: i128,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+	Io(io::Error),
+	/// The program asked for input at a different step than recorded.
+	Mismatch { recorded: u64, requested: u64 },
+	/// The program asked for more input than recorded.
+	Exhausted { step: u64 },
+	/// The program halted without reading all recorded input.
+	Unused { remaining: usize },
+	/// A line of a replay file that isn't `step value`. Lines start at 1.
+	InvalidLine(usize),
+}
+
+impl fmt::Display for ReplayError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ReplayError::Io(e) => write!(f, "Can't read replay: {}", e),
+			ReplayError::Mismatch {
+				recorded,
+				requested,
+			} => write!(
+				f,
+				"Program asked for input at step {} but the replay has it at step {}",
+				requested, recorded
+			),
+			ReplayError::Exhausted { step } => {
+				write!(
+					f,
+					"Program asked for input at step {} but the replay is over",
+					step
+				)
+			}
+			ReplayError::Unused { remaining } => write!(
+				f,
+				"Program halted with {} recorded values left unread",
+				remaining
+			),
+			ReplayError::InvalidLine(line) => write!(f, "Invalid replay entry on line {}", line),
+		}
+	}
+}
+
+impl Error for ReplayError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ReplayError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for ReplayError {
+	fn from(e: io::Error) -> Self {
+		ReplayError::Io(e)
+	}
+}
+
+impl From<ReplayError> for IntcodeError {
+	fn from(e: ReplayError) -> Self {
+		IntcodeError::Io(io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+}
+
+/// Readers that want to know which step the program is at.
+trait StepAware: BufRead {
+	fn set_step(&mut self, step: u64);
+}
+
+fn execute_counting_steps<R, W>(
+	program: &mut [i128],
+	reader: &mut R,
+	mut writer: W,
+) -> Result<(), IntcodeError>
+where
+	R: StepAware,
+	W: Write,
+{
+	let mut idx: usize = 0;
+	let mut state = State::new();
+
+	for step in 0.. {
+		reader.set_step(step);
+		if let Opcode::Halt = execute_step(program, &mut idx, &mut state, reader, &mut writer)? {
+			break;
+		}
+	}
+	Ok(())
+}
+
+/// Logs every value read through it.
+///
+/// ## Examples
+/// ```
+/// # use day09::replay::{execute_program_recorded, execute_program_replayed, Recorder, Replay};
+/// let program = [3, 9, 3, 10, 1, 9, 10, 11, 99, 0, 0, 0];
+/// let mut recorder = Recorder::new("20\n22\n".as_bytes());
+/// execute_program_recorded(&mut program.clone(), &mut recorder, vec![]).unwrap();
+///
+/// let mut file = Vec::new();
+/// recorder.save(&mut file).unwrap();
+/// assert_eq!(String::from_utf8(file.clone()).unwrap(), "# step value\n0 20\n1 22\n");
+///
+/// let mut replay = Replay::load(&file[..]).unwrap();
+/// let mut memory = program.clone();
+/// execute_program_replayed(&mut memory, &mut replay, vec![]).unwrap();
+/// assert_eq!(memory[11], 42);
+/// ```
+pub struct Recorder<R> {
+	inner: R,
+	step: u64,
+	entries: Vec<Entry>,
+}
+
+impl<R: BufRead> Recorder<R> {
+	pub fn new(inner: R) -> Self {
+		Self {
+			inner,
+			step: 0,
+			entries: Vec::new(),
+		}
+	}
+
+	pub fn entries(&self) -> &[Entry] {
+		&self.entries
+	}
+
+	/// Write the recording in the format [`Replay::load`] reads.
+	pub fn save<W: Write>(&self, writer: W) -> io::Result<()> {
+		self.to_replay().save(writer)
+	}
+
+	pub fn to_replay(&self) -> Replay {
+		Replay::new(self.entries.clone())
+	}
+}
+
+impl<R: BufRead> Read for Recorder<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.inner.read(buf)
+	}
+}
+
+impl<R: BufRead> BufRead for Recorder<R> {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		self.inner.fill_buf()
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.inner.consume(amt)
+	}
+
+	fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+		let mut line = String::new();
+		let len = self.inner.read_line(&mut line)?;
+		// Anything that doesn't parse is the program's problem, not ours.
+		if let Ok(value) = line.trim().parse::<i128>() {
+			self.entries.push(Entry {
+				step: self.step,
+				value,
+			});
+		}
+		buf.push_str(&line);
+		Ok(len)
+	}
+}
+
+impl<R: BufRead> StepAware for Recorder<R> {
+	fn set_step(&mut self, step: u64) {
+		self.step = step;
+	}
+}
+
+/// Feeds recorded values back to a program, insisting that it asks for them at the same steps.
+///
+/// ## Examples
+/// A program that takes a detour before reading gets caught.
+/// ```
+/// # use day09::replay::{execute_program_replayed, Replay};
+/// let mut replay = Replay::load("0 20\n1 22\n".as_bytes()).unwrap();
+/// let mut program = [1101, 0, 0, 13, 3, 13, 3, 14, 1, 13, 14, 15, 99, 0, 0, 0];
+/// let error = execute_program_replayed(&mut program, &mut replay, vec![]).unwrap_err();
+/// assert_eq!(
+/// 	std::error::Error::source(&error).unwrap().to_string(),
+/// 	"Program asked for input at step 1 but the replay has it at step 0"
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Replay {
+	entries: VecDeque<Entry>,
+	step: u64,
+	buffer: Vec<u8>,
+	position: usize,
+}
+
+impl Replay {
+	/// Read a replay file: one `step value` pair per line, `#` starts a comment.
+	pub fn load<R: Read>(reader: R) -> Result<Self, ReplayError> {
+		let mut entries = VecDeque::new();
+		for (line_idx, line) in io::BufReader::new(reader).lines().enumerate() {
+			let line = line?;
+			let line = match line.find('#') {
+				Some(comment) => &line[..comment],
+				None => &line[..],
+			};
+			let mut fields = line.split_whitespace();
+			let entry = match (fields.next(), fields.next(), fields.next()) {
+				(None, _, _) => continue,
+				(Some(step), Some(value), None) => step
+					.parse::<u64>()
+					.ok()
+					.zip(value.parse::<i128>().ok())
+					.map(|(step, value)| Entry { step, value }),
+				_ => None,
+			};
+			entries.push_back(entry.ok_or(ReplayError::InvalidLine(line_idx + 1))?);
+		}
+		Ok(Self::new(entries))
+	}
+
+	/// A replay of `entries`, which must be in the order the program reads them.
+	pub fn new<E: Into<VecDeque<Entry>>>(entries: E) -> Self {
+		Self {
+			entries: entries.into(),
+			step: 0,
+			buffer: Vec::new(),
+			position: 0,
+		}
+	}
+
+	/// Write the values that haven't been read yet, in the format [`Replay::load`] reads.
+	pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+		writeln!(writer, "# step value")?;
+		for entry in self.entries.iter() {
+			writeln!(writer, "{} {}", entry.step, entry.value)?;
+		}
+		Ok(())
+	}
+
+	pub fn remaining(&self) -> usize {
+		self.entries.len()
+	}
+
+	/// Take the next value, as long as it was recorded at `step`.
+	///
+	/// For programs that aren't run by [`execute_program_replayed`], which keeps track of the
+	/// steps itself.
+	pub fn next_value(&mut self, step: u64) -> Result<i128, ReplayError> {
+		let entry = match self.entries.front() {
+			Some(entry) => *entry,
+			None => return Err(ReplayError::Exhausted { step }),
+		};
+		if entry.step != step {
+			return Err(ReplayError::Mismatch {
+				recorded: entry.step,
+				requested: step,
+			});
+		}
+		self.entries.pop_front();
+		Ok(entry.value)
+	}
+}
+
+impl Read for Replay {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let available = self.fill_buf()?;
+		let len = available.len().min(buf.len());
+		buf[..len].copy_from_slice(&available[..len]);
+		self.consume(len);
+		Ok(len)
+	}
+}
+
+impl BufRead for Replay {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		if self.position >= self.buffer.len() {
+			let value = self.next_value(self.step).map_err(into_io_error)?;
+			self.buffer = format!("{}\n", value).into_bytes();
+			self.position = 0;
+		}
+		Ok(&self.buffer[self.position..])
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.position = (self.position + amt).min(self.buffer.len());
+	}
+}
+
+impl StepAware for Replay {
+	fn set_step(&mut self, step: u64) {
+		self.step = step;
+	}
+}
+
+fn into_io_error(e: ReplayError) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Execute an Intcode program, recording all input it reads.
+///
+/// The recorder keeps what it logged even if the program fails.
+pub fn execute_program_recorded<R, W>(
+	program: &mut [i128],
+	recorder: &mut Recorder<R>,
+	writer: W,
+) -> Result<(), IntcodeError>
+where
+	R: BufRead,
+	W: Write,
+{
+	execute_counting_steps(program, recorder, writer)
+}
+
+/// Execute an Intcode program with recorded input.
+///
+/// Fails if the program asks for input at any other step than recorded, or doesn't read it all.
+pub fn execute_program_replayed<W>(
+	program: &mut [i128],
+	replay: &mut Replay,
+	writer: W,
+) -> Result<(), IntcodeError>
+where
+	W: Write,
+{
+	execute_counting_steps(program, replay, writer)?;
+	match replay.remaining() {
+		0 => Ok(()),
+		remaining => Err(ReplayError::Unused { remaining }.into()),
+	}
+}
@@ -0,0 +1,70 @@
+use std::io;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::io::{ValueReader, ValueWriter};
+use crate::{execute_program, IntcodeError};
+
+/// Run a program on its own thread, reading from `input` and writing to `output`.
+///
+/// Waiting for input blocks until a value arrives. Only once every sender of `input` is gone
+/// does the program run out of input. Halting drops `output`, closing the channel.
+/// Joining the handle yields the program's final memory or whatever error stopped it.
+///
+/// ## Examples
+/// An amplifier chain: each machine gets its phase setting first, the first one also gets a `0`.
+/// ```
+/// # use day09::threaded::spawn;
+/// # use std::sync::mpsc::channel;
+/// let program = vec![3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0];
+///
+/// let (mut inputs, mut receivers) = (Vec::new(), Vec::new());
+/// for phase in [4, 3, 2, 1, 0].iter() {
+/// 	let (sender, receiver) = channel();
+/// 	sender.send(*phase).unwrap();
+/// 	inputs.push(sender);
+/// 	receivers.push(receiver);
+/// }
+/// inputs[0].send(0).unwrap();
+///
+/// // Each machine writes to the next one's input, the last one writes to us.
+/// let (last_output, result) = channel();
+/// let outputs = inputs.drain(1..).chain(std::iter::once(last_output));
+/// let handles = receivers
+/// 	.into_iter()
+/// 	.zip(outputs)
+/// 	.map(|(input, output)| spawn(program.clone(), input, output))
+/// 	.collect::<Vec<_>>();
+///
+/// assert_eq!(result.recv().unwrap(), 43210);
+/// for handle in handles {
+/// 	assert!(handle.join().unwrap().is_ok());
+/// }
+/// ```
+///
+/// Errors come back through the handle.
+/// ```
+/// # use day09::{threaded::spawn, IntcodeError};
+/// # use std::sync::mpsc::channel;
+/// let (sender, input) = channel();
+/// let (output, _) = channel();
+/// let handle = spawn(vec![3, 0, 99], input, output);
+/// drop(sender);
+/// assert!(matches!(handle.join().unwrap(), Err(IntcodeError::EndOfInput)));
+/// ```
+pub fn spawn(
+	mut program: Vec<i128>,
+	input: Receiver<i128>,
+	output: Sender<i128>,
+) -> JoinHandle<Result<Vec<i128>, IntcodeError>> {
+	thread::spawn(move || {
+		let reader = ValueReader::new(|| Ok(input.recv().ok()));
+		let writer = ValueWriter::new(|value| {
+			output
+				.send(value)
+				.map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))
+		});
+		execute_program(&mut program, reader, writer)?;
+		Ok(program)
+	})
+}
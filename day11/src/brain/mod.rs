@@ -0,0 +1,278 @@
+use std::io;
+use std::str::FromStr;
+
+use day09::{execute_step, Opcode, State};
+
+use crate::{Color, RobotError};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Turn {
+	Left,
+	Right,
+}
+
+impl Turn {
+	fn from_code(n: i128) -> Option<Self> {
+		match n {
+			0 => Some(Turn::Left),
+			1 => Some(Turn::Right),
+			_ => None,
+		}
+	}
+}
+
+/// What to do with the panel the robot's on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Instruction {
+	pub paint: Color,
+	/// Which way to turn before moving on by one panel.
+	pub turn: Turn,
+}
+
+/// Decides what the robot does.
+pub trait Brain {
+	/// What to do on a panel of `color`, or `None` once the brain's done.
+	fn decide(&mut self, color: Color) -> Result<Option<Instruction>, RobotError>;
+}
+
+/// How many values the brain's memory holds, program included.
+pub const MEMORY_SIZE: usize = 0xFFFF;
+
+/// The brain the robot was built with, an Intcode program that's given the colour under the
+/// robot and answers with the colour to paint and the way to turn.
+///
+/// Whatever the program outputs, it's reported as an error rather than taken at face value.
+/// Memory is padded with zeros to [`MEMORY_SIZE`], as [`day09::load_program`] would, and a
+/// program that goes beyond it fails with an Intcode error too.
+///
+/// ## Examples
+/// ```
+/// # use day11::{Color, Brain, IntcodeBrain, RobotError};
+/// // Outputs 1 (paint white) and then 7, which isn't a turn.
+/// let mut brain = IntcodeBrain::new(vec![104, 1, 104, 7, 99]);
+/// assert!(matches!(
+///     brain.decide(Color::Black),
+///     Err(RobotError::InvalidTurn { value: 7, step: 1 })
+/// ));
+/// ```
+#[derive(Debug)]
+pub struct IntcodeBrain {
+	program: Vec<i128>,
+	idx: usize,
+	mind: State,
+	/// How many decisions have been made.
+	steps: usize,
+}
+
+impl IntcodeBrain {
+	pub fn new(mut program: Vec<i128>) -> Self {
+		if program.len() < MEMORY_SIZE {
+			program.resize(MEMORY_SIZE, 0);
+		}
+		Self {
+			program,
+			idx: 0,
+			mind: State::new(),
+			steps: 0,
+		}
+	}
+
+	/// Run the program until it's output a number, or `None` if it halts first.
+	fn next_value(&mut self, color: Color) -> Result<Option<i128>, RobotError> {
+		let mut mind_output: Vec<u8> = vec![];
+		loop {
+			// Way too complicated to get it to accept our data.
+			let data = color.to_code().to_string();
+			let mut mind_input = io::BufReader::new(data.as_bytes());
+
+			match execute_step(
+				&mut self.program,
+				&mut self.idx,
+				&mut self.mind,
+				&mut mind_input,
+				&mut mind_output,
+			)? {
+				Opcode::Halt => return Ok(None),
+				Opcode::Output => break,
+				_ => (),
+			}
+		}
+		// Each value is written on its own line.
+		let text = String::from_utf8_lossy(&mind_output);
+		let text = text.trim();
+		text.parse()
+			.map(Some)
+			.map_err(|_| RobotError::InvalidOutput {
+				output: text.to_string(),
+				step: self.steps + 1,
+			})
+	}
+}
+
+impl Brain for IntcodeBrain {
+	fn decide(&mut self, color: Color) -> Result<Option<Instruction>, RobotError> {
+		let step = self.steps + 1;
+		let paint = match self.next_value(color)? {
+			Some(value) => {
+				Color::from_code(value).ok_or(RobotError::InvalidPaint { value, step })?
+			}
+			None => return Ok(None),
+		};
+		let turn = match self.next_value(color)? {
+			Some(value) => Turn::from_code(value).ok_or(RobotError::InvalidTurn { value, step })?,
+			None => return Ok(None),
+		};
+		self.steps = step;
+		Ok(Some(Instruction { paint, turn }))
+	}
+}
+
+/// A Langton's ant: a fixed instruction for each colour.
+///
+/// Written as a turn for each colour in the order of their codes, black then white, the ant
+/// paints each panel the other colour. `"RL"` is the classic ant.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+	on_black: Instruction,
+	on_white: Instruction,
+}
+
+impl RuleTable {
+	pub fn new(on_black: Instruction, on_white: Instruction) -> Self {
+		Self { on_black, on_white }
+	}
+}
+
+impl Brain for RuleTable {
+	fn decide(&mut self, color: Color) -> Result<Option<Instruction>, RobotError> {
+		Ok(Some(match color {
+			Color::Black => self.on_black,
+			Color::White => self.on_white,
+		}))
+	}
+}
+
+impl FromStr for RuleTable {
+	type Err = String;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let turns = text
+			.chars()
+			.map(|letter| match letter {
+				'L' => Ok(Turn::Left),
+				'R' => Ok(Turn::Right),
+				_ => Err(format!("Unknown turn `{}` in rules `{}`", letter, text)),
+			})
+			.collect::<Result<Vec<Turn>, String>>()?;
+		match turns[..] {
+			[on_black, on_white] => Ok(Self::new(
+				Instruction {
+					paint: Color::White,
+					turn: on_black,
+				},
+				Instruction {
+					paint: Color::Black,
+					turn: on_white,
+				},
+			)),
+			_ => Err(format!("Rules need a turn for each colour, not `{}`", text)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use day09::IntcodeError;
+
+	#[test]
+	fn it_reads_whole_numbers() {
+		// Outputs 1 and 0 for every input, then halts.
+		let mut brain = IntcodeBrain::new(vec![3, 9, 104, 1, 104, 0, 99, 0, 0, 0]);
+		assert_eq!(
+			brain.decide(Color::Black).unwrap(),
+			Some(Instruction {
+				paint: Color::White,
+				turn: Turn::Left,
+			})
+		);
+		assert_eq!(brain.decide(Color::White).unwrap(), None);
+	}
+
+	#[test]
+	fn it_names_the_value_and_step_of_bad_paint() {
+		// Paints black and turns right, then paints with -12.
+		let program = vec![104, 0, 104, 1, 104, -12, 104, 0, 99];
+		let mut brain = IntcodeBrain::new(program);
+		brain.decide(Color::Black).unwrap();
+		let error = brain.decide(Color::Black).unwrap_err();
+		assert!(matches!(
+			error,
+			RobotError::InvalidPaint {
+				value: -12,
+				step: 2
+			}
+		));
+		assert_eq!(error.to_string(), "Invalid paint colour -12 in step 2");
+
+		let mut brain = IntcodeBrain::new(vec![104, 10, 104, 0, 99]);
+		assert!(matches!(
+			brain.decide(Color::Black),
+			Err(RobotError::InvalidPaint { value: 10, step: 1 })
+		));
+	}
+
+	#[test]
+	fn memory_past_the_program_reads_as_zero() {
+		// Outputs whatever is at 1000, then 1, then halts.
+		let mut brain = IntcodeBrain::new(vec![4, 1000, 104, 1, 99]);
+		assert_eq!(
+			brain.decide(Color::Black).unwrap(),
+			Some(Instruction {
+				paint: Color::Black,
+				turn: Turn::Right,
+			})
+		);
+	}
+
+	#[test]
+	fn reading_past_memory_is_an_error() {
+		let mut brain = IntcodeBrain::new(vec![4, MEMORY_SIZE as i128, 99]);
+		assert!(matches!(
+			brain.decide(Color::Black),
+			Err(RobotError::Intcode(IntcodeError::InvalidAddress(address)))
+				if address == MEMORY_SIZE as i128
+		));
+	}
+
+	#[test]
+	fn falling_off_the_end_is_an_error() {
+		let mut program = vec![0; MEMORY_SIZE - 4];
+		program.extend_from_slice(&[104, 1, 104, 0]);
+		let mut brain = IntcodeBrain::new(program);
+		brain.idx = MEMORY_SIZE - 4;
+		brain.decide(Color::Black).unwrap();
+		assert!(matches!(
+			brain.decide(Color::Black),
+			Err(RobotError::Intcode(IntcodeError::InvalidAddress(address)))
+				if address == MEMORY_SIZE as i128
+		));
+
+		// The short version runs into the zeros after it instead.
+		let mut brain = IntcodeBrain::new(vec![104, 1, 104, 0]);
+		brain.decide(Color::Black).unwrap();
+		assert!(matches!(
+			brain.decide(Color::Black),
+			Err(RobotError::Intcode(IntcodeError::UnknownOpcode(0)))
+		));
+	}
+
+	#[test]
+	fn a_crashing_program_is_an_error() {
+		let mut brain = IntcodeBrain::new(vec![42]);
+		assert!(matches!(
+			brain.decide(Color::Black),
+			Err(RobotError::Intcode(_))
+		));
+	}
+}
@@ -1,22 +1,23 @@
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
-use std::io;
+use std::str::FromStr;
 
-#[derive(Copy, Clone, Debug)]
-enum Direction {
+use day09::IntcodeError;
+
+pub mod brain;
+
+pub use brain::{Brain, Instruction, IntcodeBrain, RuleTable, Turn, MEMORY_SIZE};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+	#[default]
 	Up,
 	Left,
 	Down,
 	Right,
 }
 
-impl Default for Direction {
-	fn default() -> Self {
-		Direction::Up
-	}
-}
-
 impl Direction {
 	fn rotate_left(&self) -> Self {
 		match self {
@@ -36,6 +37,13 @@ impl Direction {
 		}
 	}
 
+	fn rotate(&self, turn: Turn) -> Self {
+		match turn {
+			Turn::Left => self.rotate_left(),
+			Turn::Right => self.rotate_right(),
+		}
+	}
+
 	fn move_in_direction(&self, position: Position, distance: i32) -> Position {
 		match self {
 			Direction::Up => Position {
@@ -58,34 +66,43 @@ impl Direction {
 	}
 }
 
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
-struct Position {
-	x: i32,
-	y: i32,
-}
+impl FromStr for Direction {
+	type Err = String;
 
-impl Default for Position {
-	fn default() -> Self {
-		Self { x: 0, y: 0 }
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		Ok(match name {
+			"up" => Direction::Up,
+			"left" => Direction::Left,
+			"down" => Direction::Down,
+			"right" => Direction::Right,
+			_ => return Err(format!("Unknown heading `{}`", name)),
+		})
 	}
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Color {
+/// A panel on the hull. Up is towards positive `y`.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Position {
+	pub x: i32,
+	pub y: i32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Color {
 	White,
 	Black,
 }
 
 impl Color {
-	fn from_code(n: u8) -> Self {
+	fn from_code(n: i128) -> Option<Self> {
 		match n {
-			0 => Color::Black,
-			1 => Color::White,
-			_ => unimplemented!(),
+			0 => Some(Color::Black),
+			1 => Some(Color::White),
+			_ => None,
 		}
 	}
 
-	fn to_code(&self) -> u8 {
+	fn to_code(self) -> u8 {
 		match self {
 			Color::Black => 0,
 			Color::White => 1,
@@ -103,22 +120,26 @@ impl fmt::Display for Color {
 	}
 }
 
-#[derive(Debug)]
-struct Hull {
-	panels: HashMap<Position, Color>,
-}
+impl FromStr for Color {
+	type Err = String;
 
-impl Default for Hull {
-	fn default() -> Self {
-		Self {
-			panels: Default::default(),
-		}
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		Ok(match name {
+			"black" => Color::Black,
+			"white" => Color::White,
+			_ => return Err(format!("Unknown colour `{}`", name)),
+		})
 	}
 }
 
+#[derive(Debug, Default)]
+struct Hull {
+	panels: HashMap<Position, Color>,
+}
+
 impl Hull {
 	fn read_color(&self, position: &Position) -> Color {
-		*self.panels.get(position).or(Some(&Color::Black)).unwrap()
+		*self.panels.get(position).unwrap_or(&Color::Black)
 	}
 
 	fn paint(&mut self, position: Position, color: Color) {
@@ -128,28 +149,15 @@ impl Hull {
 
 impl fmt::Display for Hull {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.panels.is_empty() {
+			return Ok(());
+		}
 		let all_positions = self.panels.keys();
 		// Very convolutedly and slowly get our hull size
-		let leftmost = all_positions
-			.clone()
-			.min_by_key(|pos| pos.x)
-			.ok_or_else(|| fmt::Error)?
-			.x;
-		let rightmost = all_positions
-			.clone()
-			.max_by_key(|pos| pos.x)
-			.ok_or_else(|| fmt::Error)?
-			.x;
-		let topmost = all_positions
-			.clone()
-			.max_by_key(|pos| pos.y)
-			.ok_or_else(|| fmt::Error)?
-			.y;
-		let bottommost = all_positions
-			.clone()
-			.min_by_key(|pos| pos.y)
-			.ok_or_else(|| fmt::Error)?
-			.y;
+		let leftmost = all_positions.clone().map(|pos| pos.x).min().unwrap();
+		let rightmost = all_positions.clone().map(|pos| pos.x).max().unwrap();
+		let topmost = all_positions.clone().map(|pos| pos.y).max().unwrap();
+		let bottommost = all_positions.clone().map(|pos| pos.y).min().unwrap();
 
 		let width = (rightmost - leftmost + 1) as usize;
 		for y in (bottommost..=topmost).rev() {
@@ -164,98 +172,220 @@ impl fmt::Display for Hull {
 	}
 }
 
-#[derive(Debug, Copy, Clone)]
-enum InstructionType {
-	Paint,
-	Turn,
+/// Steps are counted from 1.
+#[derive(Debug)]
+pub enum RobotError {
+	Intcode(IntcodeError),
+	/// Output that isn't a whole number.
+	InvalidOutput {
+		output: String,
+		step: usize,
+	},
+	/// A number that's neither 0 for black nor 1 for white.
+	InvalidPaint {
+		value: i128,
+		step: usize,
+	},
+	/// A number that's neither 0 for left nor 1 for right.
+	InvalidTurn {
+		value: i128,
+		step: usize,
+	},
 }
 
-impl Default for InstructionType {
-	fn default() -> Self {
-		InstructionType::Paint
+impl fmt::Display for RobotError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RobotError::Intcode(e) => write!(f, "The robot's program crashed: {}", e),
+			RobotError::InvalidOutput { output, step } => {
+				write!(f, "Invalid output `{}` in step {}", output, step)
+			}
+			RobotError::InvalidPaint { value, step } => {
+				write!(f, "Invalid paint colour {} in step {}", value, step)
+			}
+			RobotError::InvalidTurn { value, step } => {
+				write!(f, "Invalid turn {} in step {}", value, step)
+			}
+		}
 	}
 }
 
-impl InstructionType {
-	fn alternate(&self) -> Self {
+impl Error for RobotError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
 		match self {
-			InstructionType::Paint => InstructionType::Turn,
-			InstructionType::Turn => InstructionType::Paint,
+			RobotError::Intcode(e) => Some(e),
+			_ => None,
 		}
 	}
 }
 
-#[derive(Debug, Default)]
-pub struct Robot {
+impl From<IntcodeError> for RobotError {
+	fn from(e: IntcodeError) -> Self {
+		RobotError::Intcode(e)
+	}
+}
+
+/// What the robot did to the hull.
+#[derive(Debug)]
+pub struct PaintReport {
+	/// How many times the robot painted each panel.
+	pub paint_counts: HashMap<Position, usize>,
+	/// How many panels the robot painted and moved on from.
+	pub steps: usize,
+	/// Whether the brain stopped on its own, rather than at the step limit.
+	pub finished: bool,
+	/// The hull as it looks afterwards, with the panel the robot started on.
+	pub image: String,
+}
+
+impl PaintReport {
+	/// How many panels were painted at least once.
+	pub fn panels_painted(&self) -> usize {
+		self.paint_counts.len()
+	}
+}
+
+/// A hull painting robot: it looks at the panel under it, paints it, turns and moves on, as
+/// its brain tells it.
+///
+/// ## Examples
+/// Langton's ant, turning right on black panels and left on white ones.
+/// ```
+/// # use day11::{Robot, RuleTable};
+/// let rules: RuleTable = "RL".parse().unwrap();
+/// let report = Robot::new(rules).step_limit(5).paint().unwrap();
+/// // Round a square and back onto the first panel.
+/// assert_eq!(report.panels_painted(), 4);
+/// assert_eq!(report.steps, 5);
+/// assert!(!report.finished);
+/// ```
+#[derive(Debug)]
+pub struct Robot<B: Brain> {
 	position: Position,
 	direction: Direction,
-	mind: day09::State,
+	brain: B,
 	hull: Hull,
-	next_instruction_type: InstructionType,
+	start_color: Color,
+	step_limit: Option<usize>,
 }
 
-impl Robot {
-	pub fn dry_run(&mut self, program: &mut [i128]) -> Result<usize, Box<dyn Error>> {
-		let mut idx = 0usize;
-		let mut mind_output: &mut Vec<u8> = &mut vec![];
-
-		loop {
-			// Way too complicated to get it to accept our data.
-			let data = self.read_from_camera().to_string();
-			let slice = data.as_bytes();
-			let mut mind_input = &mut io::BufReader::new(slice);
-
-			match day09::execute_step(
-				program,
-				&mut idx,
-				&mut self.mind,
-				&mut mind_input,
-				&mut mind_output,
-			)? {
-				day09::Opcode::Halt => break,
-				day09::Opcode::Output => self.respond_to_instructions(&mut mind_output),
-				_ => (),
-			}
+impl<B: Brain> Robot<B> {
+	/// A robot at the origin, facing up, on a black panel, that keeps going until its brain
+	/// stops.
+	pub fn new(brain: B) -> Self {
+		Self {
+			position: Position::default(),
+			direction: Direction::default(),
+			brain,
+			hull: Hull::default(),
+			start_color: Color::Black,
+			step_limit: None,
 		}
-		Ok(self.hull.panels.len())
 	}
 
-	pub fn run(&mut self, program: &mut [i128]) -> Result<String, Box<dyn Error>> {
-		// Start on a white panel instead.
-		self.hull.paint(self.position, Color::White);
-		self.dry_run(program)?;
-		Ok(self.hull.to_string())
+	/// The colour of the panel the robot starts on.
+	pub fn start_color(mut self, color: Color) -> Self {
+		self.start_color = color;
+		self
+	}
+
+	pub fn heading(mut self, direction: Direction) -> Self {
+		self.direction = direction;
+		self
 	}
 
-	fn read_from_camera(&self) -> u8 {
-		self.hull.read_color(&self.position).to_code()
+	/// Stop after this many steps, even if the brain hasn't. Brains like [`RuleTable`] never
+	/// stop on their own.
+	pub fn step_limit(mut self, steps: usize) -> Self {
+		self.step_limit = Some(steps);
+		self
 	}
 
-	fn respond_to_instructions(&mut self, instructions: &mut Vec<u8>) {
-		let raw = match instructions.remove(0) {
-			num if num.is_ascii_digit() => num - 0x30,
-			num => panic!("Invalid instruction {} provided!", num),
+	pub fn paint(mut self) -> Result<PaintReport, RobotError> {
+		self.hull.paint(self.position, self.start_color);
+		let mut paint_counts = HashMap::new();
+		let mut steps = 0;
+		let finished = loop {
+			if self.step_limit == Some(steps) {
+				break false;
+			}
+			let camera = self.hull.read_color(&self.position);
+			let instruction = match self.brain.decide(camera)? {
+				Some(instruction) => instruction,
+				None => break true,
+			};
+			self.hull.paint(self.position, instruction.paint);
+			*paint_counts.entry(self.position).or_insert(0) += 1;
+			self.direction = self.direction.rotate(instruction.turn);
+			self.move_forward();
+			steps += 1;
 		};
-		// Each instruction is followed by a newline character.
-		instructions.remove(0);
-
-		match self.next_instruction_type {
-			InstructionType::Paint => self.hull.paint(self.position, Color::from_code(raw)),
-			InstructionType::Turn => {
-				let dir = match raw {
-					0 => self.direction.rotate_left(),
-					1 => self.direction.rotate_right(),
-					_ => panic!("Invalid instructions provided!"),
-				};
-				self.direction = dir;
-				self.move_forward();
+		Ok(PaintReport {
+			paint_counts,
+			steps,
+			finished,
+			image: self.hull.to_string(),
+		})
+	}
+
+	fn move_forward(&mut self) {
+		self.position = self.direction.move_in_direction(self.position, 1);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Paints every panel white and turns right, for as many steps as it's told.
+	struct Square(usize);
+
+	impl Brain for Square {
+		fn decide(&mut self, _: Color) -> Result<Option<Instruction>, RobotError> {
+			if self.0 == 0 {
+				return Ok(None);
 			}
+			self.0 -= 1;
+			Ok(Some(Instruction {
+				paint: Color::White,
+				turn: Turn::Right,
+			}))
 		}
+	}
 
-		self.next_instruction_type = self.next_instruction_type.alternate();
+	#[test]
+	fn the_heading_decides_where_the_square_goes() {
+		let report = Robot::new(Square(4)).paint().unwrap();
+		assert!(report.finished);
+		assert_eq!(report.image, "░░\n░░\n");
+		assert!(report.paint_counts.contains_key(&Position { x: 1, y: -1 }));
+
+		let report = Robot::new(Square(4))
+			.heading(Direction::Down)
+			.paint()
+			.unwrap();
+		assert!(report.paint_counts.contains_key(&Position { x: -1, y: 1 }));
 	}
 
-	fn move_forward(&mut self) {
-		self.position = self.direction.move_in_direction(self.position, 1);
+	#[test]
+	fn the_start_panel_shows_but_is_not_painted() {
+		let report = Robot::new(Square(0))
+			.start_color(Color::White)
+			.paint()
+			.unwrap();
+		assert_eq!(report.panels_painted(), 0);
+		assert_eq!(report.image, "░\n");
+		assert_eq!(Robot::new(Square(0)).paint().unwrap().image, "█\n");
+	}
+
+	#[test]
+	fn langtons_ant_comes_back_to_the_start() {
+		let report = Robot::new("RL".parse::<RuleTable>().unwrap())
+			.step_limit(5)
+			.paint()
+			.unwrap();
+		assert_eq!(report.paint_counts[&Position::default()], 2);
+		// The first panel has been painted white and back to black.
+		assert_eq!(report.image, "█░\n░░\n");
 	}
 }
@@ -1,18 +1,67 @@
-use day09;
-use day11::Robot;
+use day11::{Brain, Color, Direction, IntcodeBrain, Robot, RuleTable};
+use std::env;
 use std::error::Error;
 
+const USAGE: &str =
+	"Usage: day11 [--rules <turns>] [--start black|white] [--heading up|down|left|right]
+             [--steps <limit>]
+
+Without options, answers both parts with the Intcode program in input.txt.
+--rules paints with a Langton's ant instead, like `RL`, for --steps steps (11000 by default).";
+
 fn main() -> Result<(), Box<dyn Error>> {
-	let mut program = day09::load_program("input.txt", 0xFFFF)?;
-	let mut robot: Robot = Default::default();
+	let (mut rules, mut start, mut heading, mut steps) = (None, None, Direction::Up, None);
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--rules" => rules = Some(args.next().ok_or(USAGE)?.parse::<RuleTable>()?),
+			"--start" => start = Some(args.next().ok_or(USAGE)?.parse::<Color>()?),
+			"--heading" => heading = args.next().ok_or(USAGE)?.parse()?,
+			"--steps" => steps = Some(args.next().ok_or(USAGE)?.parse()?),
+			_ => return Err(format!("Unknown option `{}`\n{}", arg, USAGE).into()),
+		}
+	}
+
+	if let Some(rules) = rules {
+		let robot = configure(Robot::new(rules), start, heading, steps.or(Some(11000)));
+		let report = robot.paint()?;
+		println!("{}", report.image);
+		println!(
+			"The ant painted {} panels in {} steps.",
+			report.panels_painted(),
+			report.steps
+		);
+		return Ok(());
+	}
 
-	let panels_painted = robot.dry_run(&mut program.clone())?;
-	println!("{} panels would be painted at least once!", panels_painted);
+	let program = day09::load_program("input.txt", 0xFFFF)?;
+	let robot = Robot::new(IntcodeBrain::new(program.clone()));
+	let report = configure(robot, start, heading, steps).paint()?;
+	println!(
+		"{} panels would be painted at least once!",
+		report.panels_painted()
+	);
 
-	robot = Default::default();
-	let painting = robot.run(&mut program)?;
+	let robot = Robot::new(IntcodeBrain::new(program));
+	let report = configure(robot, start.or(Some(Color::White)), heading, steps).paint()?;
 	println!("Painted on our hull:");
-	println!("{}", painting);
+	println!("{}", report.image);
 
 	Ok(())
 }
+
+fn configure<B: Brain>(
+	mut robot: Robot<B>,
+	start: Option<Color>,
+	heading: Direction,
+	steps: Option<usize>,
+) -> Robot<B> {
+	robot = robot.heading(heading);
+	if let Some(color) = start {
+		robot = robot.start_color(color);
+	}
+	if let Some(steps) = steps {
+		robot = robot.step_limit(steps);
+	}
+	robot
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use day09::memory::Patch;
+use day09::{execute_step, parse_instruction, Opcode, State};
+
+use crate::{ArcadeError, Decider, Instruction, Move, OutputParser, Screen, Tile};
+
+/// What the game looked like after a frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame {
+	/// Frames are counted from 0, the screen before the first move.
+	pub number: usize,
+	pub score: i32,
+	pub ball: Option<(i32, i32)>,
+	pub paddle: Option<(i32, i32)>,
+	pub blocks: usize,
+	pub halted: bool,
+}
+
+/// An arcade cabinet running the game, without any screen attached.
+///
+/// A frame lasts from one joystick read to the next.
+/// Cloning the cabinet takes a snapshot of the game, including the machine running it.
+///
+/// ## Examples
+/// A game that draws a block and a paddle, waits for one move and halts.
+/// ```
+/// # use day13::{Arcade, Move};
+/// let program = vec![
+///     104, 1, 104, 2, 104, 2, 104, 3, 104, 4, 104, 3, 3, 21, 104, -1, 104, 0, 104, 7, 99, 0,
+/// ];
+/// let mut arcade = Arcade::new(program).unwrap();
+/// assert_eq!(arcade.blocks(), 1);
+/// assert_eq!(arcade.paddle(), Some((3, 4)));
+///
+/// let frame = arcade.step_frame(Move::Left).unwrap();
+/// assert_eq!((frame.number, frame.score, frame.halted), (1, 7, true));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Arcade {
+	program: Vec<i128>,
+	idx: usize,
+	state: State,
+	/// Instructions executed so far.
+	steps: u64,
+	canvas: HashMap<(i32, i32), Tile>,
+	width: i32,
+	height: i32,
+	score: i32,
+	ball: Option<(i32, i32)>,
+	paddle: Option<(i32, i32)>,
+	blocks: usize,
+	frame: usize,
+	halted: bool,
+	parser: OutputParser,
+}
+
+impl Arcade {
+	/// Boot the game and run it until it wants the first move.
+	pub fn new(program: Vec<i128>) -> Result<Self, ArcadeError> {
+		let mut arcade = Self {
+			program,
+			idx: 0,
+			state: State::new(),
+			steps: 0,
+			canvas: HashMap::new(),
+			width: 0,
+			height: 0,
+			score: 0,
+			ball: None,
+			paddle: None,
+			blocks: 0,
+			frame: 0,
+			halted: false,
+			parser: OutputParser::new(),
+		};
+		arcade.run(None)?;
+		Ok(arcade)
+	}
+
+	/// Boot the game with two quarters inserted, so it can actually be played.
+	pub fn free_play(mut program: Vec<i128>) -> Result<Self, ArcadeError> {
+		let mut coins = Patch::new();
+		coins.set(0, 2);
+		coins.apply(&mut program)?;
+		Self::new(program)
+	}
+
+	/// Move the joystick and run the game until it wants the next move or halts.
+	///
+	/// Once the game has halted, this doesn't do anything.
+	pub fn step_frame(&mut self, input: Move) -> Result<Frame, ArcadeError> {
+		if !self.halted {
+			self.run(Some(input))?;
+			self.frame += 1;
+		}
+		Ok(self.frame())
+	}
+
+	pub fn frame(&self) -> Frame {
+		Frame {
+			number: self.frame,
+			score: self.score,
+			ball: self.ball,
+			paddle: self.paddle,
+			blocks: self.blocks,
+			halted: self.halted,
+		}
+	}
+
+	/// How many instructions the machine has executed, the way [`day09::replay`] counts them.
+	pub fn steps(&self) -> u64 {
+		self.steps
+	}
+
+	pub fn score(&self) -> i32 {
+		self.score
+	}
+
+	pub fn ball(&self) -> Option<(i32, i32)> {
+		self.ball
+	}
+
+	pub fn paddle(&self) -> Option<(i32, i32)> {
+		self.paddle
+	}
+
+	pub fn blocks(&self) -> usize {
+		self.blocks
+	}
+
+	pub fn is_halted(&self) -> bool {
+		self.halted
+	}
+
+	/// The tile at a position, anything not drawn yet is empty.
+	pub fn tile(&self, position: (i32, i32)) -> Tile {
+		self.canvas.get(&position).copied().unwrap_or(Tile::Empty)
+	}
+
+	/// Width and height of the area drawn on so far.
+	pub fn size(&self) -> (i32, i32) {
+		(self.width, self.height)
+	}
+
+	fn wants_input(&self) -> bool {
+		match self.program.get(self.idx) {
+			Some(value) if *value >= 0 => {
+				matches!(parse_instruction(*value as u128), Ok((Opcode::Input, _)))
+			}
+			_ => false,
+		}
+	}
+
+	/// Run until the game wants a move we don't have, or halts.
+	///
+	/// Output is applied as soon as it's written.
+	fn run(&mut self, mut input: Option<Move>) -> Result<(), ArcadeError> {
+		let mut output = Vec::new();
+		loop {
+			let mut line = String::new();
+			if self.wants_input() {
+				match input.take() {
+					Some(input) => line = format!("{}\n", input.value()),
+					None => return Ok(()),
+				}
+			}
+			output.clear();
+			let opcode = execute_step(
+				&mut self.program,
+				&mut self.idx,
+				&mut self.state,
+				&mut line.as_bytes(),
+				&mut output,
+			)?;
+			self.steps += 1;
+			if !output.is_empty() {
+				let text = String::from_utf8_lossy(&output);
+				let value = text
+					.trim()
+					.parse::<i32>()
+					.map_err(|_| ArcadeError::InvalidOutput(format!("`{}`", text.trim())))?;
+				if let Some(instruction) = self.parser.push(value)? {
+					self.apply(instruction);
+				}
+			}
+			if opcode == Opcode::Halt {
+				self.halted = true;
+				return Ok(());
+			}
+		}
+	}
+
+	fn apply(&mut self, instruction: Instruction) {
+		let (position, tile) = match instruction {
+			Instruction::Score(score) => {
+				self.score = score;
+				return;
+			}
+			Instruction::DrawTile(position, tile) => (position, tile),
+		};
+
+		let previous = self.canvas.insert(position, tile);
+		if previous == Some(Tile::Block) {
+			self.blocks -= 1;
+		}
+		match tile {
+			Tile::Block => self.blocks += 1,
+			Tile::Ball => self.ball = Some(position),
+			Tile::HorizontalPaddle => self.paddle = Some(position),
+			_ => (),
+		}
+		if tile != Tile::Ball && self.ball == Some(position) {
+			self.ball = None;
+		}
+		if tile != Tile::HorizontalPaddle && self.paddle == Some(position) {
+			self.paddle = None;
+		}
+		self.width = self.width.max(position.0 + 1);
+		self.height = self.height.max(position.1 + 1);
+	}
+}
+
+impl fmt::Display for Arcade {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "Score: {}", self.score)?;
+		for y in 0..self.height {
+			for x in 0..self.width {
+				write!(f, "{}", self.tile((x, y)))?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+/// Let `decider` play until the game halts, drawing every frame on `screen` if there is one.
+///
+/// Returns the last frame.
+pub fn play(
+	arcade: &mut Arcade,
+	decider: &mut dyn Decider,
+	mut screen: Option<&mut dyn Screen>,
+) -> Result<Frame, ArcadeError> {
+	loop {
+		if let Some(screen) = screen.as_mut() {
+			screen.draw(arcade)?;
+		}
+		if arcade.is_halted() {
+			return Ok(arcade.frame());
+		}
+		let input = decider.decide_on_move(arcade)?;
+		arcade.step_frame(input)?;
+	}
+}
+
+/// How a decider did in a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+	pub frames: usize,
+	pub inputs: Vec<Move>,
+	pub score: i32,
+	pub blocks_left: usize,
+	/// Whether all blocks were cleared.
+	pub won: bool,
+}
+
+/// Let `decider` play until the game halts, without drawing anything, and report how it did.
+pub fn evaluate(arcade: &mut Arcade, decider: &mut dyn Decider) -> Result<Report, ArcadeError> {
+	let mut inputs = Vec::new();
+	while !arcade.is_halted() {
+		let input = decider.decide_on_move(arcade)?;
+		inputs.push(input);
+		arcade.step_frame(input)?;
+	}
+	let frame = arcade.frame();
+	Ok(Report {
+		frames: frame.number,
+		inputs,
+		score: frame.score,
+		blocks_left: frame.blocks,
+		won: frame.blocks == 0,
+	})
+}
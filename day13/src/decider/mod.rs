@@ -0,0 +1,76 @@
+use std::io::{self, BufRead};
+
+use crate::{Arcade, ArcadeError, Move};
+
+mod predictor;
+mod solver;
+
+pub use predictor::Predictor;
+pub use solver::{Solution, Solver};
+
+/// Something that works the joystick.
+pub trait Decider {
+	/// Pick the move for the next frame, looking at the game as it is now.
+	fn decide_on_move(&mut self, arcade: &Arcade) -> Result<Move, ArcadeError>;
+}
+
+/// Follows the ball around, always a frame behind.
+#[derive(Debug, Default)]
+pub struct AI {
+	previous_ball_position: Option<(i32, i32)>,
+}
+
+impl Decider for AI {
+	fn decide_on_move(&mut self, arcade: &Arcade) -> Result<Move, ArcadeError> {
+		let (ball_position, player_position) = match (arcade.ball(), arcade.paddle()) {
+			(Some(ball), Some(paddle)) => (ball, paddle),
+			_ => return Ok(Move::Stay),
+		};
+
+		let next_move = if self.previous_ball_position.is_some() {
+			// >0 -> ball to my right; <0 -> ball to my left; =0 -> ball above me
+			match (ball_position.0 - player_position.0).signum() {
+				-1 => Move::Left,
+				1 => Move::Right,
+				_ => Move::Stay,
+			}
+		} else {
+			// We don't react on the first turn. This works in practice.
+			Move::Stay
+		};
+		self.previous_ball_position = Some(ball_position);
+		Ok(next_move)
+	}
+}
+
+/// Reads moves from lines of text: the Left/Right arrows or nothing, confirmed with Enter.
+#[derive(Debug)]
+pub struct Human<R> {
+	reader: R,
+}
+
+impl<R: BufRead> Human<R> {
+	pub fn new(reader: R) -> Self {
+		Self { reader }
+	}
+}
+
+impl<R: BufRead> Decider for Human<R> {
+	fn decide_on_move(&mut self, _: &Arcade) -> Result<Move, ArcadeError> {
+		let mut line = String::new();
+		loop {
+			line.clear();
+			if self.reader.read_line(&mut line)? == 0 {
+				return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "No more moves").into());
+			}
+			match line.trim_end_matches(&['\r', '\n'][..]) {
+				"\u{1b}[D" => return Ok(Move::Left),
+				"\u{1b}[C" => return Ok(Move::Right),
+				"" => return Ok(Move::Stay),
+				_ => {
+					println!("Move with the Left/Right arrows (or don't), then confirm with Enter.")
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use crate::{Arcade, ArcadeError, Decider, Move, Tile};
+
+/// Works out where the ball will come down and waits for it there.
+///
+/// The ball moves one step diagonally per frame and bounces off walls and blocks, breaking the
+/// blocks it hits. Following its path on the current screen tells us where it reaches the row
+/// above the paddle, long before it gets there.
+///
+/// ## Examples
+/// ```
+/// # use day13::{decider::Predictor, evaluate, Arcade};
+/// let program = day09::load_program("input.txt", 0xFFFF).unwrap();
+/// let report = evaluate(&mut Arcade::free_play(program).unwrap(), &mut Predictor::default()).unwrap();
+/// assert!(report.won);
+/// assert_eq!(report.frames, report.inputs.len());
+/// ```
+#[derive(Debug, Default)]
+pub struct Predictor {
+	previous_ball_position: Option<(i32, i32)>,
+}
+
+impl Predictor {
+	/// Where the paddle has to be when the ball comes down next.
+	pub fn target(&self, arcade: &Arcade) -> Option<i32> {
+		let (ball, paddle, previous) =
+			match (arcade.ball(), arcade.paddle(), self.previous_ball_position) {
+				(Some(ball), Some(paddle), Some(previous)) => (ball, paddle, previous),
+				_ => return None,
+			};
+		let velocity = (ball.0 - previous.0, ball.1 - previous.1);
+		if velocity.0.abs() != 1 || velocity.1.abs() != 1 {
+			return None;
+		}
+		landing(arcade, ball, velocity, paddle.1)
+	}
+}
+
+/// Follow the ball until it's right above the paddle's row, moving down.
+fn landing(
+	arcade: &Arcade,
+	mut ball: (i32, i32),
+	mut velocity: (i32, i32),
+	paddle_row: i32,
+) -> Option<i32> {
+	let (width, height) = arcade.size();
+	let mut broken = HashSet::new();
+	let solid = |position: (i32, i32), broken: &HashSet<(i32, i32)>| {
+		position.1 < paddle_row
+			&& match arcade.tile(position) {
+				Tile::Wall => true,
+				Tile::Block => !broken.contains(&position),
+				_ => false,
+			}
+	};
+
+	// Every position and direction only comes up once, unless the ball is stuck in a loop.
+	for _ in 0..width * height * 4 {
+		if ball.1 == paddle_row - 1 && velocity.1 > 0 {
+			return Some(ball.0);
+		}
+
+		let beside = (ball.0 + velocity.0, ball.1);
+		let above_or_below = (ball.0, ball.1 + velocity.1);
+		let mut bounced = false;
+		if solid(beside, &broken) {
+			broken.insert(beside);
+			velocity.0 = -velocity.0;
+			bounced = true;
+		}
+		if solid(above_or_below, &broken) {
+			broken.insert(above_or_below);
+			velocity.1 = -velocity.1;
+			bounced = true;
+		}
+		let diagonal = (ball.0 + velocity.0, ball.1 + velocity.1);
+		if !bounced && solid(diagonal, &broken) {
+			broken.insert(diagonal);
+			velocity = (-velocity.0, -velocity.1);
+			continue;
+		}
+		if bounced {
+			continue;
+		}
+		ball = diagonal;
+	}
+	None
+}
+
+impl Decider for Predictor {
+	fn decide_on_move(&mut self, arcade: &Arcade) -> Result<Move, ArcadeError> {
+		let (ball, paddle) = match (arcade.ball(), arcade.paddle()) {
+			(Some(ball), Some(paddle)) => (ball, paddle),
+			_ => return Ok(Move::Stay),
+		};
+		// Until we know where the ball goes, we follow it.
+		let target = self.target(arcade).unwrap_or(ball.0);
+		self.previous_ball_position = Some(ball);
+
+		Ok(match (target - paddle.0).signum() {
+			-1 => Move::Left,
+			1 => Move::Right,
+			_ => Move::Stay,
+		})
+	}
+}
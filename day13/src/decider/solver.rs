@@ -0,0 +1,412 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+use day09::replay::{Entry, Replay};
+
+use crate::recording::Recording;
+use crate::{Arcade, ArcadeError, Decider, Frame, Move, Tile};
+
+/// Searches for the winning sequence with the fewest moves by trying them on snapshots of the
+/// arcade.
+///
+/// The ball flies the same way whatever the paddle does, until it comes down next to the paddle.
+/// Where exactly the paddle is at that moment decides whether the ball is lost, and if not, which
+/// way it bounces off. So at every bounce the solver tries each place the paddle can still reach
+/// in time, going there straight, and keeps the ones that save the ball. Snapshots are explored
+/// best-first, those reached with the fewest moves first, so the first win found has the fewest
+/// moves there are. Branches that lose the ball, or come back to a snapshot that was already
+/// explored, are dropped.
+///
+/// The number of snapshots grows quickly with the number of blocks, and each one holds a copy of
+/// the machine, so the search gives up after exploring [`Solver::DEFAULT_LIMIT`] of them, or as
+/// many as set with [`Solver::with_limit`]. The puzzle's full game is out of reach.
+///
+/// As a [`Decider`], it looks for a winning sequence the first time it's asked for a move and then
+/// plays it. It expects to make all moves from then on.
+///
+/// ## Examples
+/// The puzzle's game, with all but three blocks cleared away.
+/// ```
+/// # use day13::{decider::Solver, evaluate, recording::replay, Arcade, ArcadeError, Predictor};
+/// # use day09::memory::Patch;
+/// let mut program = day09::load_program("input.txt", 0xFFFF).unwrap();
+/// // The screen starts at 639, 38 tiles to a row. 387 counts the blocks left.
+/// let keep = [639 + 2 * 38 + 2, 639 + 5 * 38 + 20, 639 + 9 * 38 + 30];
+/// let mut patch = Patch::new();
+/// for address in 639..639 + 22 * 38 {
+///     if program[address] == 2 && !keep.contains(&address) {
+///         patch.set(address, 0);
+///     }
+/// }
+/// patch.set(387, keep.len() as i128);
+/// patch.apply(&mut program).unwrap();
+/// let arcade = Arcade::free_play(program).unwrap();
+/// assert_eq!(arcade.blocks(), 3);
+///
+/// let solution = Solver::new().winning_sequence(&arcade).unwrap().unwrap();
+/// let search = Solver::new().with_limit(1).winning_sequence(&arcade);
+/// assert!(matches!(search, Err(ArcadeError::SearchLimit(1))));
+/// let predicted = evaluate(&mut arcade.clone(), &mut Predictor::default()).unwrap();
+/// assert!(predicted.won);
+/// let moves = predicted.inputs.iter().filter(|input| **input != day13::Move::Stay);
+/// assert!(solution.moves() <= moves.count());
+///
+/// let mut recording = solution.recording(&arcade).unwrap();
+/// let frame = replay(&mut arcade.clone(), &mut recording, None).unwrap();
+/// assert_eq!((frame.number, frame.score, frame.blocks), (solution.frames, solution.score, 0));
+///
+/// let report = evaluate(&mut arcade.clone(), &mut Solver::new()).unwrap();
+/// assert!(report.won);
+/// assert_eq!(report.inputs, solution.inputs);
+/// ```
+#[derive(Debug)]
+pub struct Solver {
+	limit: usize,
+	/// The frame the plan starts at, and the moves to make.
+	plan: Option<(usize, Vec<Move>)>,
+}
+
+impl Default for Solver {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A way to win the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+	/// The moves to make, starting at the frame the game was solved from.
+	pub inputs: Vec<Move>,
+	/// The frame the game ends on.
+	pub frames: usize,
+	pub score: i32,
+}
+
+impl Solution {
+	/// How many times the paddle moves.
+	pub fn moves(&self) -> usize {
+		self.inputs
+			.iter()
+			.filter(|input| **input != Move::Stay)
+			.count()
+	}
+
+	/// The solution as a recording that [`replay`](crate::recording::replay) can play back on
+	/// `arcade`, the game it was solved from, with the score there and at the end as checkpoints.
+	pub fn recording(&self, arcade: &Arcade) -> Result<Recording, ArcadeError> {
+		let scores = vec![
+			(arcade.frame().number, arcade.score()),
+			(self.frames, self.score),
+		];
+		let mut arcade = arcade.clone();
+		let mut entries = Vec::with_capacity(self.inputs.len());
+		for input in self.inputs.iter() {
+			entries.push(Entry {
+				step: arcade.steps(),
+				value: input.value() as i128,
+			});
+			arcade.step_frame(*input)?;
+		}
+		Ok(Recording {
+			moves: Replay::new(entries),
+			scores,
+		})
+	}
+}
+
+/// A snapshot of the game at the start, or right after the ball left the paddle.
+#[derive(Clone)]
+struct Node {
+	arcade: Arcade,
+	/// Where the ball was a frame earlier, which tells which way it's going.
+	previous_ball: Option<(i32, i32)>,
+}
+
+/// The ball, where it was a frame earlier, the paddle and the blocks left.
+type Key = (
+	Option<(i32, i32)>,
+	Option<(i32, i32)>,
+	Option<(i32, i32)>,
+	Vec<(i32, i32)>,
+);
+
+impl Node {
+	/// Everything that decides how the game goes on from the snapshot. The score doesn't.
+	fn key(&self) -> Key {
+		let arcade = &self.arcade;
+		let (width, height) = arcade.size();
+		let blocks = (0..height)
+			.flat_map(|y| (0..width).map(move |x| (x, y)))
+			.filter(|position| arcade.tile(*position) == Tile::Block)
+			.collect();
+		(arcade.ball(), self.previous_ball, arcade.paddle(), blocks)
+	}
+}
+
+/// What happens to the ball when the paddle stays out of its way.
+enum Flight {
+	/// The game is won on the way, this is the last frame.
+	Won(Frame),
+	/// The ball comes down above the paddle's row in this frame and column.
+	Landing {
+		frame: usize,
+		column: i32,
+	},
+	Lost,
+}
+
+/// One way to play from a node to the next.
+struct Branch {
+	node: Node,
+	inputs: Vec<Move>,
+}
+
+/// A snapshot waiting to be explored, and how the search got there.
+struct Candidate {
+	node: Node,
+	inputs: Vec<Move>,
+	moves: usize,
+	blocks: usize,
+	/// When it was found, so that candidates that are otherwise equal keep their order.
+	order: usize,
+}
+
+impl Candidate {
+	/// Fewest moves first, then fewest blocks left.
+	fn rank(&self) -> (usize, usize, usize) {
+		(self.moves, self.blocks, self.order)
+	}
+}
+
+impl PartialEq for Candidate {
+	fn eq(&self, other: &Self) -> bool {
+		self.rank() == other.rank()
+	}
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Candidate {
+	/// Reversed, so the best candidate is on top of a [`BinaryHeap`].
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.rank().cmp(&self.rank())
+	}
+}
+
+impl Solver {
+	/// How many snapshots a solver explores by default before giving up.
+	pub const DEFAULT_LIMIT: usize = 1000;
+
+	pub fn new() -> Self {
+		Self {
+			limit: Self::DEFAULT_LIMIT,
+			plan: None,
+		}
+	}
+
+	/// Give up after exploring `limit` snapshots.
+	pub fn with_limit(mut self, limit: usize) -> Self {
+		self.limit = limit;
+		self
+	}
+
+	/// Search for the winning sequence with the fewest moves, starting at the game as it is now.
+	///
+	/// Returns `None` if there's none, because the ball can't be saved whatever we do, and
+	/// [`ArcadeError::SearchLimit`] if the search gave up before it found out.
+	pub fn winning_sequence(&self, arcade: &Arcade) -> Result<Option<Solution>, ArcadeError> {
+		let start = arcade.frame().number;
+		let mut explored = HashSet::new();
+		let mut candidates = BinaryHeap::new();
+		candidates.push(Candidate {
+			node: Node {
+				arcade: arcade.clone(),
+				previous_ball: None,
+			},
+			inputs: Vec::new(),
+			moves: 0,
+			blocks: arcade.blocks(),
+			order: 0,
+		});
+		let mut found = 1;
+
+		while let Some(candidate) = candidates.pop() {
+			if !explored.insert(candidate.node.key()) {
+				continue;
+			}
+			if explored.len() > self.limit {
+				return Err(ArcadeError::SearchLimit(self.limit));
+			}
+			let (frame, column) = match fly(&candidate.node)? {
+				Flight::Won(Frame { number, score, .. }) => {
+					let mut inputs = candidate.inputs;
+					inputs.resize(number - start, Move::Stay);
+					return Ok(Some(Solution {
+						inputs,
+						frames: number,
+						score,
+					}));
+				}
+				Flight::Landing { frame, column } => (frame, column),
+				Flight::Lost => continue,
+			};
+			for branch in branches(&candidate.node, frame, column)? {
+				if explored.contains(&branch.node.key()) {
+					continue;
+				}
+				let moves = branch.inputs.iter().filter(|input| **input != Move::Stay);
+				let mut inputs = candidate.inputs.clone();
+				inputs.extend(branch.inputs.iter().copied());
+				candidates.push(Candidate {
+					moves: candidate.moves + moves.count(),
+					blocks: branch.node.arcade.blocks(),
+					node: branch.node,
+					inputs,
+					order: found,
+				});
+				found += 1;
+			}
+		}
+		Ok(None)
+	}
+}
+
+/// How long a ball can fly without coming down, on a screen of this size.
+fn flight_limit(arcade: &Arcade) -> usize {
+	let (width, height) = arcade.size();
+	(width * height * 4) as usize
+}
+
+/// Let the ball fly, without moving the paddle, until it comes down next to the paddle.
+fn fly(node: &Node) -> Result<Flight, ArcadeError> {
+	let mut arcade = node.arcade.clone();
+	let mut previous = node.previous_ball;
+	let limit = arcade.frame().number + flight_limit(&arcade);
+	loop {
+		let frame = arcade.frame();
+		if frame.halted {
+			return Ok(if frame.blocks == 0 {
+				Flight::Won(frame)
+			} else {
+				Flight::Lost
+			});
+		}
+		let (ball, paddle) = match (frame.ball, frame.paddle) {
+			(Some(ball), Some(paddle)) => (ball, paddle),
+			_ => return Ok(Flight::Lost),
+		};
+		let falling = previous.is_some_and(|previous| previous.1 < ball.1);
+		if falling && ball.1 == paddle.1 - 1 {
+			return Ok(Flight::Landing {
+				frame: frame.number,
+				column: ball.0,
+			});
+		}
+		if ball.1 >= paddle.1 || frame.number > limit {
+			return Ok(Flight::Lost);
+		}
+		previous = Some(ball);
+		arcade.step_frame(Move::Stay)?;
+	}
+}
+
+/// The ways to keep the ball in play when it comes down in `column` at frame `landing`.
+///
+/// The paddle can save it right below, or with its corner on either side.
+fn branches(node: &Node, landing: usize, column: i32) -> Result<Vec<Branch>, ArcadeError> {
+	let start = node.arcade.frame().number;
+	let paddle = match node.arcade.paddle() {
+		Some(paddle) => paddle,
+		None => return Ok(Vec::new()),
+	};
+	let mut targets: Vec<i32> = (column - 1..=column + 1)
+		.filter(|target| node.arcade.tile((*target, paddle.1)) != Tile::Wall)
+		// The paddle can still move while the ball comes down the last row.
+		.filter(|target| (target - paddle.0).unsigned_abs() as usize <= landing - start + 1)
+		.collect();
+	targets.sort_by_key(|target| (target - paddle.0).abs());
+
+	let mut branches: Vec<Branch> = Vec::new();
+	for target in targets {
+		if let Some(branch) = steer(node, target, landing)? {
+			if branches
+				.iter()
+				.all(|other| other.node.key() != branch.node.key())
+			{
+				branches.push(branch);
+			}
+		}
+	}
+	Ok(branches)
+}
+
+/// Move the paddle straight to `target` and see if the ball comes back up after `landing`.
+fn steer(node: &Node, target: i32, landing: usize) -> Result<Option<Branch>, ArcadeError> {
+	let mut arcade = node.arcade.clone();
+	let mut previous = arcade.ball();
+	let mut inputs = Vec::new();
+	loop {
+		let paddle = match arcade.paddle() {
+			Some(paddle) => paddle,
+			None => return Ok(None),
+		};
+		let input = match (target - paddle.0).signum() {
+			-1 => Move::Left,
+			1 => Move::Right,
+			_ => Move::Stay,
+		};
+		inputs.push(input);
+		let frame = arcade.step_frame(input)?;
+
+		if frame.halted {
+			if frame.blocks != 0 {
+				return Ok(None);
+			}
+		} else {
+			let (ball, paddle) = match (frame.ball, frame.paddle) {
+				(Some(ball), Some(paddle)) => (ball, paddle),
+				_ => return Ok(None),
+			};
+			if ball.1 >= paddle.1 {
+				return Ok(None);
+			}
+			let rising = previous.is_some_and(|previous| ball.1 < previous.1);
+			if frame.number <= landing || !rising {
+				previous = Some(ball);
+				continue;
+			}
+		}
+		return Ok(Some(Branch {
+			node: Node {
+				arcade,
+				previous_ball: previous,
+			},
+			inputs,
+		}));
+	}
+}
+
+impl Decider for Solver {
+	fn decide_on_move(&mut self, arcade: &Arcade) -> Result<Move, ArcadeError> {
+		let frame = arcade.frame().number;
+		let planned = |(start, inputs): &(usize, Vec<Move>)| {
+			frame
+				.checked_sub(*start)
+				.and_then(|idx| inputs.get(idx).copied())
+		};
+		if let Some(input) = self.plan.as_ref().and_then(planned) {
+			return Ok(input);
+		}
+		let solution = self
+			.winning_sequence(arcade)?
+			.ok_or(ArcadeError::Unsolvable)?;
+		self.plan = Some((frame, solution.inputs));
+		Ok(self.plan.as_ref().and_then(planned).unwrap_or(Move::Stay))
+	}
+}
@@ -0,0 +1,105 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::render::{Ascii, Renderer};
+use crate::{Arcade, Screen, Tile};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+	/// The score and the board in plain ASCII.
+	Text,
+	/// A binary PPM picture of the board, each tile a square of pixels.
+	Ppm,
+}
+
+impl ExportFormat {
+	fn extension(self) -> &'static str {
+		match self {
+			ExportFormat::Text => "txt",
+			ExportFormat::Ppm => "ppm",
+		}
+	}
+}
+
+/// Pixels per tile side in PPM pictures.
+const SCALE: usize = 8;
+
+fn color(tile: Tile) -> [u8; 3] {
+	match tile {
+		Tile::Empty => [0, 0, 0],
+		Tile::Wall => [128, 128, 128],
+		Tile::Block => [220, 120, 40],
+		Tile::HorizontalPaddle => [240, 240, 240],
+		Tile::Ball => [240, 220, 0],
+	}
+}
+
+/// Writes every frame to a file of its own, numbered from 0: `frame-00000.txt` and so on.
+///
+/// ## Examples
+/// ```
+/// # use day13::{export::{ExportFormat, FrameExporter}, Arcade, Screen};
+/// let dir = std::env::temp_dir().join("day13-export-example");
+/// let arcade = Arcade::new(vec![104, 1, 104, 0, 104, 2, 104, -1, 104, 0, 104, 5, 99]).unwrap();
+/// let mut exporter = FrameExporter::new(&dir, ExportFormat::Text).unwrap();
+/// exporter.draw(&arcade).unwrap();
+/// let text = std::fs::read_to_string(dir.join("frame-00000.txt")).unwrap();
+/// assert_eq!(text, "Score: 5\n *\n");
+/// # std::fs::remove_dir_all(dir).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FrameExporter {
+	directory: PathBuf,
+	format: ExportFormat,
+	next: usize,
+}
+
+impl FrameExporter {
+	/// Export frames into `directory`, creating it if needed.
+	pub fn new<P: Into<PathBuf>>(directory: P, format: ExportFormat) -> io::Result<Self> {
+		let directory = directory.into();
+		fs::create_dir_all(&directory)?;
+		Ok(Self {
+			directory,
+			format,
+			next: 0,
+		})
+	}
+
+	fn text(arcade: &Arcade) -> io::Result<Vec<u8>> {
+		let mut text = Vec::new();
+		Ascii.write_frame(arcade, &mut text)?;
+		Ok(text)
+	}
+
+	fn ppm(arcade: &Arcade) -> Vec<u8> {
+		let (width, height) = arcade.size();
+		let (width, height) = (width.max(0) as usize, height.max(0) as usize);
+		let mut image = format!("P6\n{} {}\n255\n", width * SCALE, height * SCALE).into_bytes();
+		for y in 0..height * SCALE {
+			for x in 0..width * SCALE {
+				let tile = arcade.tile(((x / SCALE) as i32, (y / SCALE) as i32));
+				image.extend_from_slice(&color(tile));
+			}
+		}
+		image
+	}
+}
+
+impl Screen for FrameExporter {
+	fn draw(&mut self, arcade: &Arcade) -> io::Result<()> {
+		let contents = match self.format {
+			ExportFormat::Text => Self::text(arcade)?,
+			ExportFormat::Ppm => Self::ppm(arcade),
+		};
+		let path = self.directory.join(format!(
+			"frame-{:05}.{}",
+			self.next,
+			self.format.extension()
+		));
+		fs::File::create(path)?.write_all(&contents)?;
+		self.next += 1;
+		Ok(())
+	}
+}
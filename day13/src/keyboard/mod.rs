@@ -0,0 +1,159 @@
+use std::io::{self, Stdout, Write};
+use std::panic;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use termion::event::Key;
+use termion::input::{Keys, TermRead};
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::{cursor, AsyncReader};
+
+use crate::render::Renderer;
+use crate::{Arcade, ArcadeError, Decider, DiffScreen, Move, Screen};
+
+/// The terminal in raw mode, so single key presses reach us without waiting for Enter.
+///
+/// Dropping it, or panicking while it exists, puts the terminal back the way it was.
+pub struct Terminal {
+	raw: Arc<RawTerminal<Stdout>>,
+	screen: DiffScreen<RawLines<Stdout>>,
+	/// Puts back the panic hook that was there before ours.
+	restore_hook: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl Terminal {
+	/// Take over the terminal, drawing the game with `renderer`.
+	pub fn new(renderer: Box<dyn Renderer>) -> io::Result<Self> {
+		let raw = Arc::new(io::stdout().into_raw_mode()?);
+
+		// The default hook prints the message, which is unreadable in raw mode.
+		let previous_hook = Arc::new(panic::take_hook());
+		let hook_raw = Arc::clone(&raw);
+		let hook_previous = Arc::clone(&previous_hook);
+		panic::set_hook(Box::new(move |info| {
+			restore(&hook_raw);
+			hook_previous(info);
+		}));
+		let restore_hook = Box::new(move || {
+			// Drops ours, and with it the only other handle on the previous hook.
+			let _ = panic::take_hook();
+			match Arc::try_unwrap(previous_hook) {
+				Ok(hook) => panic::set_hook(hook),
+				Err(hook) => panic::set_hook(Box::new(move |info| hook(info))),
+			}
+		});
+
+		let mut output = io::stdout();
+		write!(output, "{}", cursor::Hide)?;
+		output.flush()?;
+		Ok(Self {
+			raw,
+			screen: DiffScreen::with_renderer(RawLines(output), renderer),
+			restore_hook: Some(restore_hook),
+		})
+	}
+}
+
+/// Ends lines with `\r\n`, as raw mode doesn't go back to the start of the line by itself.
+struct RawLines<W>(W);
+
+impl<W: Write> Write for RawLines<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		for line in buf.split_inclusive(|byte| *byte == b'\n') {
+			match line.strip_suffix(b"\n") {
+				Some(line) => {
+					self.0.write_all(line)?;
+					self.0.write_all(b"\r\n")?;
+				}
+				None => self.0.write_all(line)?,
+			}
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.0.flush()
+	}
+}
+
+fn restore(raw: &RawTerminal<Stdout>) {
+	let _ = raw.suspend_raw_mode();
+	let mut output = io::stdout();
+	let _ = write!(output, "{}", cursor::Show);
+	let _ = output.flush();
+}
+
+impl Drop for Terminal {
+	fn drop(&mut self) {
+		if let Some(restore_hook) = self.restore_hook.take() {
+			restore_hook();
+		}
+		restore(&self.raw);
+	}
+}
+
+impl Screen for Terminal {
+	fn draw(&mut self, arcade: &Arcade) -> io::Result<()> {
+		self.screen.draw(arcade)
+	}
+}
+
+/// Plays along with the keys pressed, reading them as they come.
+///
+/// The game advances at least once per tick. Left and Right arrows, or `a` and `d`, move the
+/// paddle right away. If no key was pressed within a tick, the paddle stays.
+/// `p` or Space pause and resume, `q`, Escape or Ctrl-C quit.
+///
+/// Only works while there's a [`Terminal`].
+pub struct Keyboard {
+	keys: Keys<AsyncReader>,
+	tick: Duration,
+}
+
+/// How often we look for new keys while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+impl Keyboard {
+	pub fn new(tick: Duration) -> Self {
+		Self {
+			keys: termion::async_stdin().keys(),
+			tick,
+		}
+	}
+
+	fn wait_while_paused(&mut self) -> Result<(), ArcadeError> {
+		loop {
+			match self.keys.next().transpose()? {
+				Some(Key::Char('p')) | Some(Key::Char(' ')) => return Ok(()),
+				Some(key) if is_quit(key) => return Err(ArcadeError::Quit),
+				Some(_) => (),
+				None => thread::sleep(POLL_INTERVAL),
+			}
+		}
+	}
+}
+
+fn is_quit(key: Key) -> bool {
+	matches!(key, Key::Char('q') | Key::Esc | Key::Ctrl('c'))
+}
+
+impl Decider for Keyboard {
+	fn decide_on_move(&mut self, _: &Arcade) -> Result<Move, ArcadeError> {
+		let deadline = Instant::now() + self.tick;
+		while Instant::now() < deadline {
+			match self.keys.next().transpose()? {
+				Some(Key::Left) | Some(Key::Char('a')) => return Ok(Move::Left),
+				Some(Key::Right) | Some(Key::Char('d')) => return Ok(Move::Right),
+				Some(Key::Char('p')) | Some(Key::Char(' ')) => {
+					self.wait_while_paused()?;
+					return Ok(Move::Stay);
+				}
+				Some(key) if is_quit(key) => return Err(ArcadeError::Quit),
+				Some(_) => (),
+				None => thread::sleep(POLL_INTERVAL),
+			}
+		}
+		Ok(Move::Stay)
+	}
+}
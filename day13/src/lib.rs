@@ -0,0 +1,219 @@
+use std::{convert::TryFrom, error::Error, fmt, fmt::Display};
+
+use day09::IntcodeError;
+
+pub mod arcade;
+pub mod decider;
+pub mod export;
+pub mod keyboard;
+pub mod recording;
+pub mod render;
+pub mod screen;
+
+pub use arcade::{evaluate, play, Arcade, Frame, Report};
+pub use decider::{Decider, Human, Predictor, Solver, AI};
+pub use screen::{DiffScreen, Screen};
+
+#[derive(Debug)]
+pub enum ArcadeError {
+	Intcode(IntcodeError),
+	/// Output that's neither a tile to draw nor a score.
+	InvalidOutput(String),
+	Io(std::io::Error),
+	/// The player left the game.
+	Quit,
+	Replay(recording::ReplayError),
+	/// There's no way to win the game from where it is.
+	Unsolvable,
+	/// The search for a way to win gave up after exploring this many snapshots of the game.
+	SearchLimit(usize),
+}
+
+impl Display for ArcadeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ArcadeError::Intcode(e) => write!(f, "The game crashed: {}", e),
+			ArcadeError::InvalidOutput(reason) => write!(f, "Invalid game output: {}", reason),
+			ArcadeError::Io(e) => write!(f, "{}", e),
+			ArcadeError::Quit => write!(f, "The player quit"),
+			ArcadeError::Replay(e) => write!(f, "{}", e),
+			ArcadeError::Unsolvable => write!(f, "The game can't be won from here"),
+			ArcadeError::SearchLimit(limit) => {
+				write!(
+					f,
+					"No way to win found within {} snapshots of the game",
+					limit
+				)
+			}
+		}
+	}
+}
+
+impl Error for ArcadeError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ArcadeError::Intcode(e) => Some(e),
+			ArcadeError::Io(e) => Some(e),
+			ArcadeError::Replay(e) => Some(e),
+			ArcadeError::InvalidOutput(_)
+			| ArcadeError::Quit
+			| ArcadeError::Unsolvable
+			| ArcadeError::SearchLimit(_) => None,
+		}
+	}
+}
+
+impl From<IntcodeError> for ArcadeError {
+	fn from(e: IntcodeError) -> Self {
+		ArcadeError::Intcode(e)
+	}
+}
+
+impl From<std::io::Error> for ArcadeError {
+	fn from(e: std::io::Error) -> Self {
+		ArcadeError::Io(e)
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Move {
+	Stay,
+	Left,
+	Right,
+}
+
+impl Move {
+	/// The joystick position as the game reads it.
+	pub fn value(self) -> i32 {
+		match self {
+			Move::Left => -1,
+			Move::Stay => 0,
+			Move::Right => 1,
+		}
+	}
+}
+
+impl TryFrom<i32> for Move {
+	type Error = String;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
+		Ok(match value {
+			-1 => Move::Left,
+			0 => Move::Stay,
+			1 => Move::Right,
+			_ => return Err(format!("Unexpected value {}", value)),
+		})
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instruction {
+	DrawTile((i32, i32), Tile),
+	Score(i32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Tile {
+	Empty,
+	Wall,
+	Block,
+	HorizontalPaddle,
+	Ball,
+}
+
+impl Display for Tile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", render::Emoji::symbol(*self))
+	}
+}
+
+impl TryFrom<i32> for Tile {
+	type Error = String;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
+		match value {
+			0 => Ok(Tile::Empty),
+			1 => Ok(Tile::Wall),
+			2 => Ok(Tile::Block),
+			3 => Ok(Tile::HorizontalPaddle),
+			4 => Ok(Tile::Ball),
+			_ => Err(format!("Unexpected tile value {}", value)),
+		}
+	}
+}
+
+/// Turns the game's output into instructions, one value at a time.
+///
+/// ## Examples
+/// ```
+/// # use day13::{Instruction, OutputParser};
+/// let mut parser = OutputParser::new();
+/// assert_eq!(parser.push(-1).unwrap(), None);
+/// assert_eq!(parser.push(0).unwrap(), None);
+/// assert_eq!(parser.push(12).unwrap(), Some(Instruction::Score(12)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OutputParser {
+	pending: Vec<i32>,
+}
+
+impl OutputParser {
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	/// Take the next value, returning the instruction it completes.
+	pub fn push(&mut self, value: i32) -> Result<Option<Instruction>, ArcadeError> {
+		self.pending.push(value);
+		let instruction = match self.pending[..] {
+			[-1, 0, score] => Instruction::Score(score),
+			[x, y, code] => Instruction::DrawTile(
+				(x, y),
+				Tile::try_from(code).map_err(ArcadeError::InvalidOutput)?,
+			),
+			_ => return Ok(None),
+		};
+		self.pending.clear();
+		Ok(Some(instruction))
+	}
+
+	/// Whether there are values left over that don't make an instruction yet.
+	pub fn is_incomplete(&self) -> bool {
+		!self.pending.is_empty()
+	}
+}
+
+/// Turn the game's output, one value per line, into instructions.
+///
+/// ## Examples
+/// ```
+/// # use day13::{parse_output, Instruction, Tile};
+/// let instructions = parse_output(b"1\n2\n3\n-1\n0\n12\n").unwrap();
+/// assert_eq!(
+///     instructions,
+///     [
+///         Instruction::DrawTile((1, 2), Tile::HorizontalPaddle),
+///         Instruction::Score(12)
+///     ]
+/// );
+/// assert!(parse_output(b"1\n2\n").is_err());
+/// ```
+pub fn parse_output(raw_output: &[u8]) -> Result<Vec<Instruction>, ArcadeError> {
+	let text_output = String::from_utf8_lossy(raw_output);
+	let mut parser = OutputParser::new();
+	let mut instructions = Vec::new();
+
+	for value in text_output
+		.lines()
+		.filter_map(|line| line.trim().parse::<i32>().ok())
+	{
+		if let Some(instruction) = parser.push(value)? {
+			instructions.push(instruction);
+		}
+	}
+	if parser.is_incomplete() {
+		return Err(ArcadeError::InvalidOutput("Leftover values!".to_string()));
+	}
+
+	Ok(instructions)
+}
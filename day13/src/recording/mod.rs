@@ -0,0 +1,265 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+use day09::replay::{self, Entry, Replay};
+
+use crate::{Arcade, ArcadeError, Decider, Frame, Move, Screen};
+
+/// How a replay went wrong. Frames are counted as in [`Frame::number`].
+#[derive(Debug)]
+pub enum ReplayError {
+	/// The game didn't ask for a move the way it did when it was recorded.
+	Diverged {
+		frame: usize,
+		cause: replay::ReplayError,
+	},
+	/// A recorded value that isn't a joystick position.
+	InvalidMove { frame: usize, value: i128 },
+	/// The game's score differs from the recorded one.
+	ScoreMismatch {
+		frame: usize,
+		recorded: i32,
+		actual: i32,
+	},
+	/// A line of a recording that's neither a move nor a score checkpoint. Lines start at 1.
+	InvalidLine(usize),
+}
+
+impl fmt::Display for ReplayError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ReplayError::Diverged { frame, cause } => {
+				write!(f, "Replay diverged at frame {}: {}", frame, cause)
+			}
+			ReplayError::InvalidMove { frame, value } => {
+				write!(f, "Invalid recorded move {} at frame {}", value, frame)
+			}
+			ReplayError::ScoreMismatch {
+				frame,
+				recorded,
+				actual,
+			} => write!(
+				f,
+				"Score is {} at frame {} but the recording has {}",
+				actual, frame, recorded
+			),
+			ReplayError::InvalidLine(line) => {
+				write!(f, "Invalid recording on line {}", line)
+			}
+		}
+	}
+}
+
+impl Error for ReplayError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ReplayError::Diverged { cause, .. } => Some(cause),
+			_ => None,
+		}
+	}
+}
+
+impl From<ReplayError> for ArcadeError {
+	fn from(e: ReplayError) -> Self {
+		ArcadeError::Replay(e)
+	}
+}
+
+/// The moves of a game as a [`day09::replay`] of the joystick, along with the score every so
+/// often to check a replay against.
+///
+/// ## Examples
+/// ```
+/// # use day13::{play, recording::{replay, Recorder, Recording}, Arcade, Predictor};
+/// let program = day09::load_program("input.txt", 0xFFFF).unwrap();
+/// let mut predictor = Predictor::default();
+/// let mut recorder = Recorder::new(&mut predictor, 500);
+/// let mut arcade = Arcade::free_play(program.clone()).unwrap();
+/// let frame = play(&mut arcade, &mut recorder, None).unwrap();
+/// let recording = recorder.finish(&arcade);
+/// assert_eq!(recording.moves.remaining(), frame.number);
+///
+/// let mut file = Vec::new();
+/// recording.save(&mut file).unwrap();
+/// let mut loaded = Recording::load(&file[..]).unwrap();
+/// assert_eq!(loaded, recording);
+///
+/// let mut arcade = Arcade::free_play(program).unwrap();
+/// assert_eq!(replay(&mut arcade, &mut loaded, None).unwrap(), frame);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+	pub moves: Replay,
+	/// Frame numbers and the score at that frame, in order.
+	pub scores: Vec<(usize, i32)>,
+}
+
+impl Recording {
+	/// Write the recording as text: `@<frame> <score>` checkpoints, then the moves in the format
+	/// [`Replay::save`] writes them.
+	pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+		writeln!(writer, "# @frame score")?;
+		for (frame, score) in self.scores.iter() {
+			writeln!(writer, "@{} {}", frame, score)?;
+		}
+		self.moves.save(writer)
+	}
+
+	/// Read a recording written by [`Recording::save`]. `#` starts a comment.
+	pub fn load<R: Read>(reader: R) -> Result<Self, ArcadeError> {
+		let mut scores = Vec::new();
+		// Checkpoints are blanked out, so the replay counts lines the same way we do.
+		let mut moves = String::new();
+		for (line_idx, line) in io::BufReader::new(reader).lines().enumerate() {
+			let line = line?;
+			let content = match line.find('#') {
+				Some(comment) => &line[..comment],
+				None => &line[..],
+			};
+			let checkpoint = match content.trim().strip_prefix('@') {
+				Some(checkpoint) => checkpoint,
+				None => {
+					moves.push_str(&line);
+					moves.push('\n');
+					continue;
+				}
+			};
+			let mut fields = checkpoint.split_whitespace();
+			let parsed = match (fields.next(), fields.next(), fields.next()) {
+				(Some(frame), Some(score), None) => frame.parse().ok().zip(score.parse().ok()),
+				_ => None,
+			};
+			scores.push(parsed.ok_or(ReplayError::InvalidLine(line_idx + 1))?);
+			moves.push('\n');
+		}
+		let moves = Replay::load(moves.as_bytes()).map_err(|e| match e {
+			replay::ReplayError::Io(e) => ArcadeError::Io(e),
+			replay::ReplayError::InvalidLine(line) => ReplayError::InvalidLine(line).into(),
+			// Loading doesn't play anything, so nothing else can go wrong.
+			cause => ReplayError::Diverged { frame: 0, cause }.into(),
+		})?;
+		Ok(Self { moves, scores })
+	}
+}
+
+/// Notes the moves another decider makes, and the score every `interval` frames.
+///
+/// Each move is kept along with the step the game's program read it at, so a replay only
+/// goes through if the game runs exactly as it did while recording.
+pub struct Recorder<'a> {
+	inner: &'a mut dyn Decider,
+	interval: usize,
+	entries: Vec<Entry>,
+	scores: Vec<(usize, i32)>,
+}
+
+impl<'a> Recorder<'a> {
+	pub fn new(inner: &'a mut dyn Decider, interval: usize) -> Self {
+		Self {
+			inner,
+			interval: interval.max(1),
+			entries: Vec::new(),
+			scores: Vec::new(),
+		}
+	}
+
+	/// The recording so far, with the current score as the last checkpoint.
+	pub fn finish(mut self, arcade: &Arcade) -> Recording {
+		let frame = arcade.frame();
+		if self.scores.last().map(|(at, _)| *at) != Some(frame.number) {
+			self.scores.push((frame.number, frame.score));
+		}
+		Recording {
+			moves: Replay::new(self.entries),
+			scores: self.scores,
+		}
+	}
+}
+
+impl Decider for Recorder<'_> {
+	fn decide_on_move(&mut self, arcade: &Arcade) -> Result<Move, ArcadeError> {
+		let frame = arcade.frame();
+		if frame.number % self.interval == 0 {
+			self.scores.push((frame.number, frame.score));
+		}
+		let input = self.inner.decide_on_move(arcade)?;
+		self.entries.push(Entry {
+			step: arcade.steps(),
+			value: input.value() as i128,
+		});
+		Ok(input)
+	}
+}
+
+/// Makes the recorded moves, as long as the game asks for them at the recorded steps, and
+/// checks the score along the way.
+struct Replayer<'a> {
+	moves: &'a mut Replay,
+	scores: &'a [(usize, i32)],
+	checkpoint: usize,
+}
+
+impl Replayer<'_> {
+	/// Compare the score with all checkpoints up to the current frame.
+	fn check(&mut self, frame: Frame) -> Result<(), ReplayError> {
+		while let Some((at, recorded)) = self.scores.get(self.checkpoint) {
+			if *at > frame.number {
+				break;
+			}
+			if *at == frame.number && *recorded != frame.score {
+				return Err(ReplayError::ScoreMismatch {
+					frame: frame.number,
+					recorded: *recorded,
+					actual: frame.score,
+				});
+			}
+			self.checkpoint += 1;
+		}
+		Ok(())
+	}
+}
+
+impl Decider for Replayer<'_> {
+	fn decide_on_move(&mut self, arcade: &Arcade) -> Result<Move, ArcadeError> {
+		let frame = arcade.frame();
+		self.check(frame)?;
+		let frame = frame.number;
+		let value = self
+			.moves
+			.next_value(arcade.steps())
+			.map_err(|cause| ReplayError::Diverged { frame, cause })?;
+		let input = i32::try_from(value)
+			.ok()
+			.and_then(|value| Move::try_from(value).ok())
+			.ok_or(ReplayError::InvalidMove { frame, value })?;
+		Ok(input)
+	}
+}
+
+/// Play a recording on a freshly booted arcade, drawing every frame on `screen` if there is one.
+///
+/// Fails as soon as the game takes a different course than recorded or its score differs from a
+/// checkpoint, or if it ends before all recorded moves were made.
+pub fn replay(
+	arcade: &mut Arcade,
+	recording: &mut Recording,
+	screen: Option<&mut dyn Screen>,
+) -> Result<Frame, ArcadeError> {
+	let mut replayer = Replayer {
+		moves: &mut recording.moves,
+		scores: &recording.scores,
+		checkpoint: 0,
+	};
+	let frame = crate::play(arcade, &mut replayer, screen)?;
+	replayer.check(frame)?;
+	match recording.moves.remaining() {
+		0 => Ok(frame),
+		remaining => Err(ReplayError::Diverged {
+			frame: frame.number,
+			cause: replay::ReplayError::Unused { remaining },
+		}
+		.into()),
+	}
+}
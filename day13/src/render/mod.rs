@@ -0,0 +1,217 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use termion::color;
+
+use crate::{Arcade, Tile};
+
+/// Turns the game into text.
+pub trait Renderer {
+	/// How many terminal columns a tile takes up, or `None` if tiles can't be drawn one at a time.
+	fn tile_width(&self) -> Option<u16>;
+
+	fn write_tile(&self, tile: Tile, out: &mut dyn Write) -> io::Result<()>;
+
+	/// The whole frame: the score on the first line, the board below it.
+	fn write_frame(&self, arcade: &Arcade, out: &mut dyn Write) -> io::Result<()> {
+		writeln!(out, "Score: {}", arcade.score())?;
+		let (width, height) = arcade.size();
+		for y in 0..height {
+			for x in 0..width {
+				self.write_tile(arcade.tile((x, y)), out)?;
+			}
+			writeln!(out)?;
+		}
+		Ok(())
+	}
+}
+
+/// Emoji, for terminals with the fonts for them. What [`Tile`]'s `Display` shows.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Emoji;
+
+impl Emoji {
+	pub fn symbol(tile: Tile) -> &'static str {
+		match tile {
+			// Empty and Wall are double-width because so are all the emoji
+			Tile::Empty => "  ",
+			Tile::Ball => "⚽",
+			Tile::Wall => "▮▮",
+			Tile::Block => "🎁",
+			Tile::HorizontalPaddle => "🏃",
+		}
+	}
+}
+
+impl Renderer for Emoji {
+	fn tile_width(&self) -> Option<u16> {
+		Some(2)
+	}
+
+	fn write_tile(&self, tile: Tile, out: &mut dyn Write) -> io::Result<()> {
+		write!(out, "{}", Self::symbol(tile))
+	}
+}
+
+/// One plain character per tile, which works anywhere, log files included.
+///
+/// ## Examples
+/// ```
+/// # use day13::{render::{Ascii, Renderer}, Arcade};
+/// let arcade = Arcade::new(vec![104, 1, 104, 0, 104, 2, 104, 0, 104, 0, 104, 1, 99]).unwrap();
+/// let mut text = Vec::new();
+/// Ascii.write_frame(&arcade, &mut text).unwrap();
+/// assert_eq!(String::from_utf8(text).unwrap(), "Score: 0\n#*\n");
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Ascii;
+
+impl Ascii {
+	pub fn symbol(tile: Tile) -> char {
+		match tile {
+			Tile::Empty => ' ',
+			Tile::Wall => '#',
+			Tile::Block => '*',
+			Tile::HorizontalPaddle => '=',
+			Tile::Ball => 'o',
+		}
+	}
+}
+
+impl Renderer for Ascii {
+	fn tile_width(&self) -> Option<u16> {
+		Some(1)
+	}
+
+	fn write_tile(&self, tile: Tile, out: &mut dyn Write) -> io::Result<()> {
+		write!(out, "{}", Self::symbol(tile))
+	}
+}
+
+/// Blocks of colour, two columns wide, for terminals without emoji.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Ansi;
+
+impl Renderer for Ansi {
+	fn tile_width(&self) -> Option<u16> {
+		Some(2)
+	}
+
+	fn write_tile(&self, tile: Tile, out: &mut dyn Write) -> io::Result<()> {
+		let background: &dyn color::Color = match tile {
+			Tile::Empty => return write!(out, "  "),
+			Tile::Wall => &color::LightBlack,
+			Tile::Block => &color::Yellow,
+			Tile::HorizontalPaddle => &color::White,
+			Tile::Ball => &color::Red,
+		};
+		write!(
+			out,
+			"{}  {}",
+			color::Bg(background),
+			color::Bg(color::Reset)
+		)
+	}
+}
+
+/// One line of JSON per frame, for other programs to read.
+///
+/// Tiles are written as the numbers the game uses for them, in rows.
+///
+/// ## Examples
+/// ```
+/// # use day13::{render::{Json, Renderer}, Arcade};
+/// let arcade = Arcade::new(vec![104, 1, 104, 0, 104, 4, 104, 0, 104, 0, 104, 1, 99]).unwrap();
+/// let mut json = Vec::new();
+/// Json.write_frame(&arcade, &mut json).unwrap();
+/// assert_eq!(
+///     String::from_utf8(json).unwrap(),
+///     r#"{"frame":0,"score":0,"ball":[1,0],"paddle":null,"blocks":0,"tiles":[[1,4]]}"#.to_owned()
+///         + "\n"
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Json;
+
+fn json_position(position: Option<(i32, i32)>) -> String {
+	match position {
+		Some((x, y)) => format!("[{},{}]", x, y),
+		None => "null".to_string(),
+	}
+}
+
+impl Renderer for Json {
+	fn tile_width(&self) -> Option<u16> {
+		None
+	}
+
+	fn write_tile(&self, tile: Tile, out: &mut dyn Write) -> io::Result<()> {
+		let code = match tile {
+			Tile::Empty => 0,
+			Tile::Wall => 1,
+			Tile::Block => 2,
+			Tile::HorizontalPaddle => 3,
+			Tile::Ball => 4,
+		};
+		write!(out, "{}", code)
+	}
+
+	fn write_frame(&self, arcade: &Arcade, out: &mut dyn Write) -> io::Result<()> {
+		let frame = arcade.frame();
+		write!(
+			out,
+			r#"{{"frame":{},"score":{},"ball":{},"paddle":{},"blocks":{},"tiles":["#,
+			frame.number,
+			frame.score,
+			json_position(frame.ball),
+			json_position(frame.paddle),
+			frame.blocks
+		)?;
+		let (width, height) = arcade.size();
+		for y in 0..height {
+			write!(out, "{}[", if y == 0 { "" } else { "," })?;
+			for x in 0..width {
+				if x > 0 {
+					write!(out, ",")?;
+				}
+				self.write_tile(arcade.tile((x, y)), out)?;
+			}
+			write!(out, "]")?;
+		}
+		writeln!(out, "]}}")
+	}
+}
+
+/// The renderers to pick from by name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Backend {
+	Emoji,
+	Ascii,
+	Ansi,
+	Json,
+}
+
+impl Backend {
+	pub fn renderer(self) -> Box<dyn Renderer> {
+		match self {
+			Backend::Emoji => Box::new(Emoji),
+			Backend::Ascii => Box::new(Ascii),
+			Backend::Ansi => Box::new(Ansi),
+			Backend::Json => Box::new(Json),
+		}
+	}
+}
+
+impl FromStr for Backend {
+	type Err = String;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		Ok(match name {
+			"emoji" => Backend::Emoji,
+			"ascii" => Backend::Ascii,
+			"ansi" => Backend::Ansi,
+			"json" => Backend::Json,
+			_ => return Err(format!("Unknown renderer `{}`", name)),
+		})
+	}
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use termion::{clear, cursor};
+
+use crate::render::{Emoji, Renderer};
+use crate::{Arcade, Tile};
+
+/// Somewhere to show the game.
+pub trait Screen {
+	fn draw(&mut self, arcade: &Arcade) -> io::Result<()>;
+}
+
+/// Any writer can show the game, one whole frame after another.
+impl<W: Write> Screen for W {
+	fn draw(&mut self, arcade: &Arcade) -> io::Result<()> {
+		write!(self, "{}", arcade)
+	}
+}
+
+/// Keeps a single picture of the game up to date on a terminal.
+///
+/// Remembers what it drew and only moves the cursor to the tiles that changed since.
+/// The score goes on the first line, the board below it. Renderers that can't draw single tiles
+/// get to write every frame in full instead.
+///
+/// ## Examples
+/// ```
+/// # use day13::{Arcade, DiffScreen, Screen};
+/// let arcade = Arcade::new(vec![104, 1, 104, 0, 104, 2, 99]).unwrap();
+/// let mut screen = DiffScreen::new(Vec::new());
+/// screen.draw(&arcade).unwrap();
+/// let first = screen.output().len();
+/// screen.draw(&arcade).unwrap();
+/// // Nothing changed, so all we did was to park the cursor again.
+/// assert!(screen.output().len() - first < 10);
+/// ```
+pub struct DiffScreen<W> {
+	output: W,
+	renderer: Box<dyn Renderer>,
+	drawn: HashMap<(i32, i32), Tile>,
+	score: Option<i32>,
+}
+
+impl<W: Write> DiffScreen<W> {
+	/// A screen drawing tiles as emoji.
+	pub fn new(output: W) -> Self {
+		Self::with_renderer(output, Box::new(Emoji))
+	}
+
+	pub fn with_renderer(output: W, renderer: Box<dyn Renderer>) -> Self {
+		Self {
+			output,
+			renderer,
+			drawn: HashMap::new(),
+			score: None,
+		}
+	}
+
+	pub fn output(&self) -> &W {
+		&self.output
+	}
+}
+
+/// Terminal coordinates start at 1, below the score.
+fn goto(position: (i32, i32), tile_width: u16) -> cursor::Goto {
+	cursor::Goto(position.0 as u16 * tile_width + 1, position.1 as u16 + 2)
+}
+
+impl<W: Write> Screen for DiffScreen<W> {
+	fn draw(&mut self, arcade: &Arcade) -> io::Result<()> {
+		let tile_width = match self.renderer.tile_width() {
+			Some(tile_width) => tile_width,
+			None => {
+				self.renderer.write_frame(arcade, &mut self.output)?;
+				return self.output.flush();
+			}
+		};
+		if self.score.is_none() {
+			write!(self.output, "{}", clear::All)?;
+		}
+		if self.score != Some(arcade.score()) {
+			write!(
+				self.output,
+				"{}Score: {}{}",
+				cursor::Goto(1, 1),
+				arcade.score(),
+				clear::UntilNewline
+			)?;
+			self.score = Some(arcade.score());
+		}
+
+		let (width, height) = arcade.size();
+		for y in 0..height {
+			for x in 0..width {
+				let tile = arcade.tile((x, y));
+				if self.drawn.insert((x, y), tile) != Some(tile) {
+					write!(self.output, "{}", goto((x, y), tile_width))?;
+					self.renderer.write_tile(tile, &mut self.output)?;
+				}
+			}
+		}
+
+		// Whatever comes after the game goes below it.
+		write!(self.output, "{}", goto((0, height), tile_width))?;
+		self.output.flush()
+	}
+}
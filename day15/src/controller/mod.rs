@@ -0,0 +1,303 @@
+use std::{convert::TryFrom, error::Error, fmt, io};
+
+use day09::IntcodeError;
+
+use crate::{io::Environment, path::Pathfinder, Direction, Feedback, GameState};
+
+#[derive(Debug)]
+pub enum ExplorationError {
+	Intcode(IntcodeError),
+	/// The program stopped while the droid was still exploring.
+	Halted,
+	/// Output that isn't a number at all.
+	InvalidOutput(String),
+	/// A number that isn't one of the movement commands.
+	InvalidCommand(i128),
+	/// A number that isn't one of the status codes.
+	InvalidStatus(i128),
+	/// A status code arrived while the controller wasn't waiting for one.
+	UnexpectedStatus(i128),
+	/// Something went wrong while showing the exploration.
+	Io(io::Error),
+}
+
+impl fmt::Display for ExplorationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ExplorationError::Intcode(e) => write!(f, "The droid's program crashed: {}", e),
+			ExplorationError::Halted => write!(f, "The droid's program stopped"),
+			ExplorationError::InvalidOutput(text) => write!(f, "Invalid droid output: {}", text),
+			ExplorationError::InvalidCommand(command) => {
+				write!(f, "Invalid movement command {}", command)
+			}
+			ExplorationError::InvalidStatus(status) => {
+				write!(f, "Invalid status code {}", status)
+			}
+			ExplorationError::UnexpectedStatus(status) => {
+				write!(f, "Status code {} without a movement command", status)
+			}
+			ExplorationError::Io(e) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl Error for ExplorationError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ExplorationError::Intcode(e) => Some(e),
+			ExplorationError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<IntcodeError> for ExplorationError {
+	fn from(e: IntcodeError) -> Self {
+		ExplorationError::Intcode(e)
+	}
+}
+
+impl From<io::Error> for ExplorationError {
+	fn from(e: io::Error) -> Self {
+		ExplorationError::Io(e)
+	}
+}
+
+/// What the droid found out.
+pub struct ExplorationResult {
+	/// The map of everything the droid could reach.
+	pub map: GameState,
+	/// How many movement commands it took.
+	pub moves: usize,
+	pub oxygen_system: Option<(i32, i32)>,
+	/// The fewest moves from the start to the oxygen system.
+	pub shortest_path: Option<usize>,
+	/// The minutes until oxygen has filled the whole area.
+	pub minutes_to_fill: Option<usize>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+	/// Ready to pick the next move.
+	Idle,
+	/// Sent a movement command and waiting for its status code.
+	Moving(Direction),
+	/// Everything's been explored.
+	Done,
+}
+
+/// Explores the whole maze, one movement command and status code at a time.
+///
+/// It heads for the first neighbouring cell it hasn't seen yet, and takes the shortest way to
+/// the nearest unexplored cell when there's none. Once there's nothing left to explore, it's done.
+///
+/// ## Examples
+/// A corridor with the oxygen system at its end.
+/// ```
+/// # use day15::{io::Environment, Controller, ExplorationError};
+/// struct Corridor(i32);
+///
+/// impl Environment for Corridor {
+///     fn send(&mut self, command: i128) -> Result<i128, ExplorationError> {
+///         // Only east (4) and back west (3) are open, two steps long.
+///         let next = match command {
+///             3 => self.0 - 1,
+///             4 => self.0 + 1,
+///             _ => return Ok(0),
+///         };
+///         if !(0..=2).contains(&next) {
+///             return Ok(0);
+///         }
+///         self.0 = next;
+///         Ok(if next == 2 { 2 } else { 1 })
+///     }
+/// }
+///
+/// let result = Controller::new().run(&mut Corridor(0)).unwrap();
+/// assert_eq!(result.oxygen_system, Some((2, 0)));
+/// assert_eq!(result.shortest_path, Some(2));
+/// assert_eq!(result.minutes_to_fill, Some(2));
+/// assert!(result.map.is_fully_explored());
+/// ```
+pub struct Controller {
+	game: GameState,
+	phase: Phase,
+	moves: usize,
+}
+
+impl Default for Controller {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Controller {
+	pub fn new() -> Self {
+		Self {
+			game: GameState::default(),
+			phase: Phase::Idle,
+			moves: 0,
+		}
+	}
+
+	/// Carry on exploring a map that's been explored before, with the droid where the map has it.
+	pub fn resume(game: GameState) -> Self {
+		Self {
+			game,
+			..Self::new()
+		}
+	}
+
+	pub fn game(&self) -> &GameState {
+		&self.game
+	}
+
+	/// How many movement commands have been answered so far.
+	pub fn moves(&self) -> usize {
+		self.moves
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.phase == Phase::Done
+	}
+
+	/// The direction to move in next, or `None` once everything's been explored.
+	///
+	/// Until the status code for a move arrives, this keeps returning that same move.
+	pub fn next_move(&mut self) -> Option<Direction> {
+		match self.phase {
+			Phase::Moving(dir) => return Some(dir),
+			Phase::Done => return None,
+			Phase::Idle => (),
+		}
+
+		let game = &self.game;
+		let unexplored = Direction::ALL
+			.iter()
+			.copied()
+			.find(|dir| game.is_unexplored(dir.step(game.droid_pos)));
+		let next = unexplored.or_else(|| {
+			Pathfinder::new(game)
+				.nearest_frontier(game.droid_pos)
+				.and_then(|path| path.directions().first().copied())
+		});
+
+		self.phase = match next {
+			Some(dir) => Phase::Moving(dir),
+			None => Phase::Done,
+		};
+		next
+	}
+
+	/// Update the map with the droid's answer to the last move.
+	pub fn handle_status(&mut self, status: i128) -> Result<(), ExplorationError> {
+		let dir = match self.phase {
+			Phase::Moving(dir) => dir,
+			_ => return Err(ExplorationError::UnexpectedStatus(status)),
+		};
+		let feedback = u8::try_from(status)
+			.ok()
+			.and_then(|code| Feedback::try_from(code).ok())
+			.ok_or(ExplorationError::InvalidStatus(status))?;
+
+		let target = dir.step(self.game.droid_pos);
+		match feedback {
+			Feedback::EncounteredWall => self.game.record_wall(target),
+			Feedback::Moved | Feedback::MovedAndFoundTarget => {
+				self.game.move_droid(target);
+				if feedback == Feedback::MovedAndFoundTarget {
+					self.game.oxygen_system_pos = Some(target);
+				}
+			}
+		}
+		self.moves += 1;
+		self.phase = Phase::Idle;
+		Ok(())
+	}
+
+	/// Explore until there's nothing left to see.
+	pub fn run<E: Environment>(
+		self,
+		environment: &mut E,
+	) -> Result<ExplorationResult, ExplorationError> {
+		self.run_with(environment, |_| Ok(()))
+	}
+
+	/// Explore like [`Controller::run`], calling `after_move` after every move, to show how
+	/// it's going.
+	pub fn run_with<E: Environment>(
+		mut self,
+		environment: &mut E,
+		mut after_move: impl FnMut(&Self) -> io::Result<()>,
+	) -> Result<ExplorationResult, ExplorationError> {
+		while let Some(dir) = self.next_move() {
+			let status = environment.send(dir.to_code().into())?;
+			self.handle_status(status)?;
+			after_move(&self)?;
+		}
+		Ok(self.finish())
+	}
+
+	pub fn finish(self) -> ExplorationResult {
+		ExplorationResult {
+			moves: self.moves,
+			oxygen_system: self.game.oxygen_system_pos(),
+			shortest_path: self.game.shortest_path_to_oxygen(),
+			minutes_to_fill: self.game.minutes_to_fill_with_oxygen(),
+			map: self.game,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Answers with the status codes it was given, in order.
+	struct Script(std::vec::IntoIter<i128>);
+
+	impl Environment for Script {
+		fn send(&mut self, _: i128) -> Result<i128, ExplorationError> {
+			Ok(self.0.next().expect("Ran out of status codes"))
+		}
+	}
+
+	#[test]
+	fn a_boxed_in_droid_is_done_after_four_walls() {
+		let mut script = Script(vec![0, 0, 0, 0].into_iter());
+		let result = Controller::new().run(&mut script).unwrap();
+		assert_eq!(result.moves, 4);
+		assert_eq!(result.oxygen_system, None);
+		assert_eq!(result.minutes_to_fill, None);
+		assert!(result.map.is_fully_explored());
+	}
+
+	#[test]
+	fn it_goes_back_after_a_dead_end() {
+		let mut controller = Controller::new();
+		assert_eq!(controller.next_move(), Some(Direction::North));
+		controller.handle_status(1).unwrap();
+		for _ in 0..3 {
+			controller.next_move();
+			controller.handle_status(0).unwrap();
+		}
+		// North, east and west of the new cell are walls, south is where we came from.
+		assert_eq!(controller.next_move(), Some(Direction::South));
+		controller.handle_status(1).unwrap();
+		assert_eq!(controller.game().droid_pos(), (0, 0));
+	}
+
+	#[test]
+	fn it_rejects_status_codes_out_of_turn() {
+		let mut controller = Controller::new();
+		assert!(matches!(
+			controller.handle_status(1),
+			Err(ExplorationError::UnexpectedStatus(1))
+		));
+		controller.next_move();
+		assert!(matches!(
+			controller.handle_status(7),
+			Err(ExplorationError::InvalidStatus(7))
+		));
+	}
+}
@@ -1,181 +1,76 @@
-use std::{collections::HashMap, convert::TryFrom, io, process::exit, sync::RwLock};
+use day09::{
+	coverage::{execute_step_with_coverage, Coverage},
+	execute_step, Opcode, State,
+};
 
-use crate::{Direction, Feedback, GameState, Tile};
+use crate::ExplorationError;
 
-pub struct AI<'a> {
-	pub game_state: &'a RwLock<GameState>,
+/// Something a droid moves around in: it takes movement commands from 1 to 4 and answers each
+/// with a status code, 0 for a wall, 1 for a step and 2 for a step onto the oxygen system.
+pub trait Environment {
+	fn send(&mut self, command: i128) -> Result<i128, ExplorationError>;
 }
 
-impl AI<'_> {
-	fn choose_direction(&self) -> Direction {
-		let mut game = self.game_state.write().unwrap();
-		let dirs: HashMap<Direction, (i32, i32)> = vec![
-			Direction::North,
-			Direction::East,
-			Direction::South,
-			Direction::West,
-		]
-		.into_iter()
-		.map(|d| (d, d.step(game.droid_pos)))
-		.filter(|(_, pos)| match game.world.get(pos) {
-			Some(Tile::Wall) => false,
-			Some(Tile::Cursed) => false,
-			_ => true,
-		})
-		.collect();
-		game.block_way_back = false;
-
-		match dirs.len() {
-			0 => panic!("We're boxed in?!"),
-			1 => {
-				game.block_way_back = true;
-				*dirs.keys().next().unwrap()
-			}
-			2 => *dirs.keys().next().unwrap(),
-			_ => *dirs
-				.keys()
-				.filter(|d| {
-					if let Some(prev) = game.previous_move {
-						**d != prev.reverse()
-					} else {
-						true
-					}
-				})
-				.next()
-				.unwrap(),
-		}
-	}
-}
-
-impl io::Read for AI<'_> {
-	#[allow(unused_variables)]
-	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-		unimplemented!()
-	}
+/// The real droid, remote controlled by an Intcode program.
+pub struct IntcodeDroid {
+	program: Vec<i128>,
+	idx: usize,
+	state: State,
+	coverage: Option<Coverage>,
 }
 
-impl io::BufRead for AI<'_> {
-	fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
-		unimplemented!()
+impl IntcodeDroid {
+	pub fn new(program: Vec<i128>) -> Self {
+		Self {
+			program,
+			idx: 0,
+			state: State::new(),
+			coverage: None,
+		}
 	}
 
-	#[allow(unused_variables)]
-	fn consume(&mut self, amt: usize) {
-		unimplemented!()
+	/// Record which parts of the program the exploration makes it run.
+	pub fn collect_coverage(mut self) -> Self {
+		self.coverage = Some(Coverage::new());
+		self
 	}
 
-	fn read_line(&mut self, buf: &mut String) -> std::io::Result<usize> {
-		let dir = self.choose_direction();
-		let msg = format!("{}\n", dir.to_code());
-		buf.push_str(&msg);
-		self.game_state.write().unwrap().previous_move = Some(dir);
-		Ok(msg.len())
+	pub fn coverage(&self) -> Option<&Coverage> {
+		self.coverage.as_ref()
 	}
 }
 
-pub struct Output<'a> {
-	pub game_state: &'a RwLock<GameState>,
-	pub framecount: u16,
-}
-
-impl io::Write for Output<'_> {
-	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-		// This function is called with just a single character each time.
-		// So in some cases, it won't be a character we're interested in
-		// but we need to say we've written a character anyway.
-
-		let text = String::from_utf8(buf.to_owned())
-			.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-		let text = text.trim();
-
-		if !text.is_empty() {
-			let feedback = Feedback::try_from(
-				u8::from_str_radix(text, 10)
-					.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
-			)
-			.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-
-			match feedback {
-				Feedback::Moved => self.step(),
-				Feedback::MovedAndFoundTarget => {
-					self.step();
-					println!("{}", self.game_state.read().unwrap());
-					println!("WE DID IT!");
-					exit(0);
-				}
-				Feedback::EncounteredWall => self.register_wall_ahead(),
+impl Environment for IntcodeDroid {
+	/// Run the program until it has read the command and written the status code.
+	fn send(&mut self, command: i128) -> Result<i128, ExplorationError> {
+		let line = format!("{}\n", command);
+		let mut input = line.as_bytes();
+		let mut output = Vec::new();
+		while !output.ends_with(b"\n") {
+			let opcode = match &mut self.coverage {
+				Some(coverage) => execute_step_with_coverage(
+					&mut self.program,
+					&mut self.idx,
+					&mut self.state,
+					&mut input,
+					&mut output,
+					coverage,
+				)?,
+				None => execute_step(
+					&mut self.program,
+					&mut self.idx,
+					&mut self.state,
+					&mut input,
+					&mut output,
+				)?,
+			};
+			if opcode == Opcode::Halt {
+				return Err(ExplorationError::Halted);
 			}
-
-			if self.framecount == 0 {
-				println!("{}", self.game_state.read().unwrap())
-			}
-			self.framecount = self.framecount.wrapping_add(1);
-		}
-
-		Ok(1)
-	}
-
-	fn flush(&mut self) -> io::Result<()> {
-		unimplemented!()
-	}
-}
-
-impl Output<'_> {
-	fn register_wall_ahead(&self) {
-		let current_position = self.game_state.read().unwrap().droid_pos;
-		let wall_position = self
-			.game_state
-			.read()
-			.unwrap()
-			.previous_move
-			.unwrap()
-			.step(current_position);
-		*self
-			.game_state
-			.write()
-			.unwrap()
-			.world
-			.entry(wall_position)
-			.or_default() = Tile::Wall;
-	}
-
-	fn step(&self) {
-		let current_position = self.game_state.read().unwrap().droid_pos;
-		let next_position = self
-			.game_state
-			.read()
-			.unwrap()
-			.previous_move
-			.unwrap()
-			.step(current_position);
-
-		let mut game = self.game_state.write().unwrap();
-		*game.world.entry(next_position).or_default() = Tile::Droid;
-		*game.world.entry(current_position).or_default() = if game.block_way_back {
-			Tile::Cursed
-		} else {
-			Tile::Traversable
-		};
-		game.droid_pos = next_position;
-
-		// Extend the world map
-		let y_coord = if next_position.1 >= 0 {
-			(Direction::South, next_position.1 as u32)
-		} else {
-			(Direction::North, -next_position.1 as u32)
-		};
-		let x_coord = if next_position.0 >= 0 {
-			(Direction::East, next_position.0 as u32)
-		} else {
-			(Direction::West, -next_position.0 as u32)
-		};
-		let y_size = game.world_size.get_mut(&y_coord.0).unwrap();
-		if y_coord.1 >= *y_size {
-			*y_size = y_coord.1 + 1
-		}
-		let x_size = game.world_size.get_mut(&x_coord.0).unwrap();
-		if x_coord.1 >= *x_size {
-			*x_size = x_coord.1 + 1
 		}
+		let text = String::from_utf8_lossy(&output);
+		text.trim()
+			.parse()
+			.map_err(|_| ExplorationError::InvalidOutput(text.trim().to_string()))
 	}
 }
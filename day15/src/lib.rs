@@ -1,10 +1,22 @@
 use ansi_term::Color;
-use std::{collections::HashMap, convert::TryFrom, fmt::Display};
+use std::{
+	collections::{HashMap, HashSet},
+	convert::TryFrom,
+	fmt::Display,
+};
 
+pub mod controller;
 pub mod io;
+pub mod map;
+pub mod maze;
+pub mod path;
+pub mod render;
 
+pub use controller::{Controller, ExplorationError, ExplorationResult};
+
+/// What the droid reports after a movement command.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Feedback {
+pub enum Feedback {
 	Moved,
 	MovedAndFoundTarget,
 	EncounteredWall,
@@ -25,7 +37,7 @@ impl TryFrom<u8> for Feedback {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Direction {
+pub enum Direction {
 	North,
 	South,
 	West,
@@ -33,7 +45,15 @@ enum Direction {
 }
 
 impl Direction {
-	fn to_code(self) -> u8 {
+	const ALL: [Direction; 4] = [
+		Direction::North,
+		Direction::East,
+		Direction::South,
+		Direction::West,
+	];
+
+	/// The movement command for this direction.
+	pub fn to_code(self) -> u8 {
 		use Direction::*;
 		match self {
 			North => 1,
@@ -43,7 +63,8 @@ impl Direction {
 		}
 	}
 
-	fn step(self, pos: (i32, i32)) -> (i32, i32) {
+	/// The position one step from `pos` in this direction.
+	pub fn step(self, pos: (i32, i32)) -> (i32, i32) {
 		use Direction::*;
 		match self {
 			West => (pos.0 - 1, pos.1),
@@ -53,7 +74,12 @@ impl Direction {
 		}
 	}
 
-	fn reverse(self) -> Self {
+	/// The direction that takes a droid from `from` to the neighbouring `to`.
+	pub fn between(from: (i32, i32), to: (i32, i32)) -> Option<Self> {
+		Self::ALL.iter().copied().find(|dir| dir.step(from) == to)
+	}
+
+	pub fn reverse(self) -> Self {
 		use Direction::*;
 		match self {
 			North => South,
@@ -64,45 +90,58 @@ impl Direction {
 	}
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 enum Tile {
+	#[default]
 	Unexplored,
 	Traversable,
 	Wall,
 	Droid,
-	Cursed,
 }
 
-impl Display for Tile {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Tile {
+	/// Whether the droid, or oxygen, can go there.
+	fn is_open(self) -> bool {
+		matches!(self, Tile::Traversable | Tile::Droid)
+	}
+}
+
+impl Tile {
+	/// The character the map shows for the tile.
+	fn glyph(self) -> char {
 		use Tile::*;
-		write!(
-			f,
-			"{}",
-			match self {
-				Unexplored => Color::Cyan.paint("?"),
-				Traversable => Color::Black.paint("."),
-				Wall => Color::White.paint("#"),
-				Droid => Color::Red.paint("X"),
-				Cursed => Color::White.dimmed().paint("#"),
-			}
-		)
+		match self {
+			Unexplored => '?',
+			Traversable => '.',
+			Wall => '#',
+			Droid => 'X',
+		}
+	}
+
+	fn colour(self) -> Color {
+		use Tile::*;
+		match self {
+			Unexplored => Color::Cyan,
+			Traversable => Color::Black,
+			Wall => Color::White,
+			Droid => Color::Red,
+		}
 	}
 }
 
-impl Default for Tile {
-	fn default() -> Self {
-		Self::Unexplored
+impl Display for Tile {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.colour().paint(self.glyph().to_string()))
 	}
 }
 
+#[derive(Clone)]
 pub struct GameState {
 	droid_starting_pos: (i32, i32),
 	droid_pos: (i32, i32),
+	oxygen_system_pos: Option<(i32, i32)>,
 	world: HashMap<(i32, i32), Tile>,
 	world_size: HashMap<Direction, u32>,
-	previous_move: Option<Direction>,
-	block_way_back: bool,
 }
 
 impl Default for GameState {
@@ -110,6 +149,7 @@ impl Default for GameState {
 		Self {
 			droid_starting_pos: (0, 0),
 			droid_pos: (0, 0),
+			oxygen_system_pos: None,
 			world: vec![((0, 0), Tile::Droid)].into_iter().collect(),
 			world_size: vec![
 				(Direction::North, 1),
@@ -119,34 +159,191 @@ impl Default for GameState {
 			]
 			.into_iter()
 			.collect(),
-			previous_move: None,
-			block_way_back: false,
 		}
 	}
 }
 
 impl Display for GameState {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		for y in -(*self.world_size.get(&Direction::North).unwrap() as i32)
-			..=(*self.world_size.get(&Direction::South).unwrap() as i32)
-		{
-			for x in -(*self.world_size.get(&Direction::West).unwrap() as i32)
-				..=(*self.world_size.get(&Direction::East).unwrap() as i32)
-			{
-				match self.world.get(&(x, y)) {
-					Some(Tile::Droid) => write!(f, "{}", Tile::Droid),
-					Some(tile) => {
-						if (x, y) == self.droid_starting_pos {
-							write!(f, "{}", Color::Yellow.paint("S"))
-						} else {
-							write!(f, "{}", *tile)
-						}
-					}
-					None => write!(f, "{}", Tile::default()),
-				}?;
+		self.draw(f, true)
+	}
+}
+
+/// A [`GameState`] drawn without colours, see [`GameState::plain`].
+pub struct Plain<'a>(&'a GameState);
+
+impl Display for Plain<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.0.draw(f, false)
+	}
+}
+
+impl GameState {
+	/// The map with the same glyphs as `Display`, but no colours.
+	pub fn plain(&self) -> Plain<'_> {
+		Plain(self)
+	}
+
+	/// The top left and bottom right corners of the map.
+	fn extent(&self) -> ((i32, i32), (i32, i32)) {
+		let size = |dir| self.world_size[&dir] as i32;
+		(
+			(-size(Direction::West), -size(Direction::North)),
+			(size(Direction::East), size(Direction::South)),
+		)
+	}
+
+	fn draw(&self, f: &mut std::fmt::Formatter<'_>, colour: bool) -> std::fmt::Result {
+		let (top_left, bottom_right) = self.extent();
+		for y in top_left.1..=bottom_right.1 {
+			for x in top_left.0..=bottom_right.0 {
+				let tile = self.world.get(&(x, y)).copied().unwrap_or_default();
+				let (glyph, color) = if tile == Tile::Droid {
+					(tile.glyph(), tile.colour())
+				} else if Some((x, y)) == self.oxygen_system_pos {
+					('O', Color::Blue)
+				} else if (x, y) == self.droid_starting_pos && tile != Tile::Unexplored {
+					('S', Color::Yellow)
+				} else {
+					(tile.glyph(), tile.colour())
+				};
+				if colour {
+					write!(f, "{}", color.paint(glyph.to_string()))?;
+				} else {
+					write!(f, "{}", glyph)?;
+				}
 			}
-			write!(f, "\n")?;
+			writeln!(f)?;
 		}
 		Ok(())
 	}
+
+	/// Whether every cell the droid can reach has been visited.
+	pub fn is_fully_explored(&self) -> bool {
+		self.world.iter().all(|(pos, tile)| {
+			!tile.is_open()
+				|| Direction::ALL
+					.iter()
+					.all(|dir| self.world.contains_key(&dir.step(*pos)))
+		})
+	}
+
+	pub fn droid_pos(&self) -> (i32, i32) {
+		self.droid_pos
+	}
+
+	/// Whether we know nothing about the cell yet.
+	fn is_unexplored(&self, pos: (i32, i32)) -> bool {
+		self.world.get(&pos).copied().unwrap_or_default() == Tile::Unexplored
+	}
+
+	fn record_wall(&mut self, pos: (i32, i32)) {
+		self.world.insert(pos, Tile::Wall);
+	}
+
+	fn move_droid(&mut self, next_position: (i32, i32)) {
+		self.world.insert(self.droid_pos, Tile::Traversable);
+		self.world.insert(next_position, Tile::Droid);
+		self.droid_pos = next_position;
+
+		// Extend the world map
+		let y_coord = if next_position.1 >= 0 {
+			(Direction::South, next_position.1 as u32)
+		} else {
+			(Direction::North, -next_position.1 as u32)
+		};
+		let x_coord = if next_position.0 >= 0 {
+			(Direction::East, next_position.0 as u32)
+		} else {
+			(Direction::West, -next_position.0 as u32)
+		};
+		let y_size = self.world_size.get_mut(&y_coord.0).unwrap();
+		if y_coord.1 >= *y_size {
+			*y_size = y_coord.1 + 1
+		}
+		let x_size = self.world_size.get_mut(&x_coord.0).unwrap();
+		if x_coord.1 >= *x_size {
+			*x_size = x_coord.1 + 1
+		}
+	}
+
+	pub fn oxygen_system_pos(&self) -> Option<(i32, i32)> {
+		self.oxygen_system_pos
+	}
+
+	/// The fewest moves from the starting position to the oxygen system, through known cells.
+	pub fn shortest_path_to_oxygen(&self) -> Option<usize> {
+		let target = self.oxygen_system_pos?;
+		self.spread_from(self.droid_starting_pos)
+			.iter()
+			.position(|cells| cells.contains(&target))
+	}
+
+	/// Simulate oxygen filling the area from the oxygen system.
+	///
+	/// Returns the cells that get oxygen in each minute, starting with the oxygen system itself
+	/// at minute 0.
+	pub fn oxygen_spread(&self) -> Vec<Vec<(i32, i32)>> {
+		match self.oxygen_system_pos {
+			Some(pos) => self.spread_from(pos),
+			None => Vec::new(),
+		}
+	}
+
+	/// The minutes until oxygen has filled every known open cell.
+	pub fn minutes_to_fill_with_oxygen(&self) -> Option<usize> {
+		self.oxygen_spread().len().checked_sub(1)
+	}
+
+	/// The open cells reachable from `start`, grouped by their distance from it.
+	fn spread_from(&self, start: (i32, i32)) -> Vec<Vec<(i32, i32)>> {
+		let mut seen: HashSet<(i32, i32)> = vec![start].into_iter().collect();
+		let mut fronts = vec![vec![start]];
+		loop {
+			let next: Vec<(i32, i32)> = fronts
+				.last()
+				.unwrap()
+				.iter()
+				.flat_map(|pos| Direction::ALL.iter().map(move |dir| dir.step(*pos)))
+				.filter(|pos| self.world.get(pos).is_some_and(|tile| tile.is_open()))
+				.filter(|pos| seen.insert(*pos))
+				.collect();
+			if next.is_empty() {
+				return fronts;
+			}
+			fronts.push(next);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A game that's seen the map, where `S` is the start, `O` the oxygen system and `.` open.
+	pub(crate) fn game_from_map(map: &str) -> GameState {
+		map.parse().unwrap()
+	}
+
+	#[test]
+	fn oxygen_fills_the_example_in_four_minutes() {
+		let game = game_from_map(
+			" ##   \n\
+			 #..## \n\
+			 #S#..#\n\
+			 #.O.# \n\
+			 \x20###  ",
+		);
+		assert_eq!(game.minutes_to_fill_with_oxygen(), Some(4));
+		assert_eq!(game.oxygen_spread()[1].len(), 2);
+		assert_eq!(game.shortest_path_to_oxygen(), Some(2));
+		assert!(game.is_fully_explored());
+	}
+
+	#[test]
+	fn unknown_cells_are_not_explored() {
+		let game = game_from_map("#S.O");
+		assert_eq!(game.shortest_path_to_oxygen(), Some(2));
+		assert!(!game.is_fully_explored());
+	}
 }
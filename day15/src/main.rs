@@ -1,20 +1,117 @@
-use std::{error::Error, sync::RwLock};
+use std::{
+	env,
+	error::Error,
+	fs::{self, File},
+};
+
+use lib::GameState;
 
 use day09 as intcode;
 use day15 as lib;
+use lib::maze::{Maze, MazeKind};
+use lib::render::{Policy, Renderer, Target};
+
+const USAGE: &str =
+	"Usage: day15 [--maze <file> | --random <seed> [--braided] [--size <width>x<height>]]
+             [--load <map> [--analyse]] [--save <map>]
+             [--render every:<moves>|fps:<frames>|discovery|never] [--no-colour] [--frames <dir>]
+             [--coverage <file>]
+
+Explores input.txt's maze with the Intcode droid, or a maze from a file or a random one.
+--load carries on exploring a saved map, or only answers the questions with --analyse.
+--save writes the explored map.
+--render draws the map while exploring, to stdout or as files in --frames' directory.
+  fps draws every move, slowing exploring down to that many moves a second.
+--coverage writes which parts of the Intcode program the exploration ran.";
 
 fn main() -> Result<(), Box<dyn Error>> {
-	let _game = lib::GameState::default();
-	let game = RwLock::new(_game);
-	let ai = lib::io::AI { game_state: &game };
-	let output = lib::io::Output {
-		game_state: &game,
-		framecount: 0,
+	let (mut maze_path, mut seed) = (None, None);
+	let (mut kind, mut size) = (MazeKind::Perfect, (41, 41));
+	let (mut load_path, mut save_path, mut analyse) = (None, None, false);
+	let (mut policy, mut colour, mut target) = (Policy::Never, true, Target::Stdout);
+	let mut coverage_path = None;
+	let mut args = env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--maze" => maze_path = Some(args.next().ok_or(USAGE)?),
+			"--random" => seed = Some(args.next().ok_or(USAGE)?.parse()?),
+			"--braided" => kind = MazeKind::Braided,
+			"--load" => load_path = Some(args.next().ok_or(USAGE)?),
+			"--save" => save_path = Some(args.next().ok_or(USAGE)?),
+			"--analyse" => analyse = true,
+			"--render" => policy = args.next().ok_or(USAGE)?.parse()?,
+			"--no-colour" => colour = false,
+			"--frames" => target = Target::Directory(args.next().ok_or(USAGE)?.into()),
+			"--coverage" => coverage_path = Some(args.next().ok_or(USAGE)?),
+			"--size" => {
+				let text = args.next().ok_or(USAGE)?;
+				let (width, height) = text.split_once('x').ok_or(USAGE)?;
+				size = (width.parse()?, height.parse()?);
+			}
+			_ => return Err(format!("Unknown option `{}`\n{}", arg, USAGE).into()),
+		}
+	}
+
+	let controller = match load_path {
+		Some(path) => {
+			let mut map = GameState::load(File::open(path)?)?;
+			// A new droid, real or not, starts where the first one did.
+			map.return_droid_to_start();
+			lib::Controller::resume(map)
+		}
+		None if analyse => return Err(format!("--analyse needs a map to --load\n{}", USAGE).into()),
+		None => lib::Controller::new(),
+	};
+	let maze = match (maze_path, seed) {
+		(Some(path), _) => Some(Maze::load(File::open(path)?)?),
+		(None, Some(seed)) => Some(Maze::generate(size.0, size.1, kind, seed)),
+		(None, None) => None,
+	};
+	let mut renderer = Renderer::new(policy).colour(colour).target(target);
+	let show =
+		|controller: &lib::Controller| renderer.update(controller.game(), controller.moves());
+	let result = match maze {
+		_ if analyse => controller.finish(),
+		Some(mut maze) => {
+			let result = controller.run_with(&mut maze, show)?;
+			println!(
+				"Visited {:.1}% of {} open cells.",
+				maze.coverage() * 100.0,
+				maze.open_cells()
+			);
+			result
+		}
+		None => {
+			let program = intcode::load_program("input.txt", 0xFFFF)?;
+			let mut droid = lib::io::IntcodeDroid::new(program.clone());
+			if coverage_path.is_some() {
+				droid = droid.collect_coverage();
+			}
+			let result = controller.run_with(&mut droid, show)?;
+			if let (Some(path), Some(coverage)) = (&coverage_path, droid.coverage()) {
+				fs::write(path, coverage.annotate(&program))?;
+				println!("Coverage: {}", coverage.summary(&program));
+			}
+			result
+		}
 	};
-	let mut program = intcode::load_program("input.txt", 0xFFFF)?;
-	intcode::execute_program(&mut program, ai, output)?;
+	if let Some(path) = save_path {
+		result.map.save(File::create(path)?)?;
+	}
 
-	println!("{}", game.read().unwrap());
+	if colour {
+		println!("{}", result.map);
+	} else {
+		println!("{}", result.map.plain());
+	}
+	println!("Explored the area in {} moves.", result.moves);
+	match result.shortest_path {
+		Some(distance) => println!("The oxygen system is {} moves away.", distance),
+		None => println!("There's no oxygen system."),
+	}
+	if let Some(minutes) = result.minutes_to_fill {
+		println!("Oxygen fills the area in {} minutes.", minutes);
+	}
 
 	Ok(())
 }
@@ -0,0 +1,277 @@
+use std::{
+	collections::HashMap,
+	error::Error,
+	fmt,
+	io::{self, Read, Write},
+	str::FromStr,
+};
+
+use crate::{Direction, GameState, Tile};
+
+#[derive(Debug)]
+pub enum MapError {
+	/// A character that's none of ` ?.#XSO`, with its 1-based line and column.
+	InvalidGlyph {
+		line: usize,
+		column: usize,
+		glyph: char,
+	},
+	/// The last line has positions, but not in the `key=x,y` form.
+	InvalidPositions(String),
+	/// There's neither an `S` nor an `X` to tell where the droid is.
+	NoStart,
+	Io(io::Error),
+}
+
+impl fmt::Display for MapError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MapError::InvalidGlyph {
+				line,
+				column,
+				glyph,
+			} => write!(
+				f,
+				"Unexpected `{}` in line {}, column {}",
+				glyph, line, column
+			),
+			MapError::InvalidPositions(line) => write!(f, "Invalid positions: {}", line),
+			MapError::NoStart => write!(f, "The map doesn't show the droid or where it started"),
+			MapError::Io(e) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl Error for MapError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			MapError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for MapError {
+	fn from(e: io::Error) -> Self {
+		MapError::Io(e)
+	}
+}
+
+/// The positions that can't be told from the glyphs alone, when the droid covers the start or
+/// the oxygen system, or the map doesn't include the origin.
+#[derive(Default)]
+struct Positions {
+	start: Option<(i32, i32)>,
+	droid: Option<(i32, i32)>,
+	oxygen_system: Option<(i32, i32)>,
+	top_left: Option<(i32, i32)>,
+}
+
+impl FromStr for Positions {
+	type Err = MapError;
+
+	fn from_str(line: &str) -> Result<Self, Self::Err> {
+		let invalid = || MapError::InvalidPositions(line.to_string());
+		let mut positions = Positions::default();
+		for field in line.split_whitespace() {
+			let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+			let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+			let pos = Some((
+				x.parse().map_err(|_| invalid())?,
+				y.parse().map_err(|_| invalid())?,
+			));
+			match key {
+				"start" => positions.start = pos,
+				"droid" => positions.droid = pos,
+				"oxygen" => positions.oxygen_system = pos,
+				"top-left" => positions.top_left = pos,
+				_ => return Err(invalid()),
+			}
+		}
+		Ok(positions)
+	}
+}
+
+impl GameState {
+	/// Write the map in the form [`GameState::load`] reads: the plain map, followed by a line
+	/// with the positions of the start, the droid, the oxygen system and the top left corner.
+	///
+	/// ## Examples
+	/// ```
+	/// # use day15::{maze::Maze, Controller, GameState};
+	/// let mut maze: Maze = "#####\n#S..#\n#.#O#\n#####".parse().unwrap();
+	/// let result = Controller::new().run(&mut maze).unwrap();
+	/// let mut file = Vec::new();
+	/// result.map.save(&mut file).unwrap();
+	///
+	/// let map = GameState::load(&file[..]).unwrap();
+	/// assert_eq!(map.droid_pos(), result.map.droid_pos());
+	/// assert_eq!(map.shortest_path_to_oxygen(), Some(3));
+	/// assert_eq!(map.plain().to_string(), result.map.plain().to_string());
+	/// ```
+	pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+		let (top_left, _) = self.extent();
+		write!(out, "{}", self.plain())?;
+		let pos = |(x, y): (i32, i32)| format!("{},{}", x, y);
+		write!(
+			out,
+			"start={} droid={}",
+			pos(self.droid_starting_pos),
+			pos(self.droid_pos)
+		)?;
+		if let Some(oxygen_system) = self.oxygen_system_pos {
+			write!(out, " oxygen={}", pos(oxygen_system))?;
+		}
+		writeln!(out, " top-left={}", pos(top_left))
+	}
+
+	/// Read a map, as written by [`GameState::save`].
+	///
+	/// The line of positions is optional, so maps can be drawn by hand. Without it, the top left
+	/// corner is at (0, 0), and `S`, `X` and `O` mark the start, the droid and the oxygen system.
+	/// Spaces count as unexplored, just like `?`.
+	pub fn load<R: Read>(mut reader: R) -> Result<Self, MapError> {
+		let mut text = String::new();
+		reader.read_to_string(&mut text)?;
+		text.parse()
+	}
+
+	/// Send the droid back to where it started, for a droid program that's been restarted.
+	pub fn return_droid_to_start(&mut self) {
+		self.move_droid(self.droid_starting_pos);
+	}
+}
+
+impl FromStr for GameState {
+	type Err = MapError;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let mut lines: Vec<&str> = text.lines().collect();
+		let positions = match lines.last() {
+			Some(line) if line.contains('=') => {
+				let positions = line.parse()?;
+				lines.pop();
+				positions
+			}
+			_ => Positions::default(),
+		};
+		let top_left = positions.top_left.unwrap_or((0, 0));
+
+		let mut world = HashMap::new();
+		let (mut start, mut droid, mut oxygen_system) = (None, None, None);
+		let mut width = 0;
+		for (y, line) in lines.iter().enumerate() {
+			for (x, glyph) in line.chars().enumerate() {
+				let pos = (top_left.0 + x as i32, top_left.1 + y as i32);
+				let tile = match glyph {
+					'?' | ' ' => continue,
+					'.' => Tile::Traversable,
+					'#' => Tile::Wall,
+					'S' => {
+						start = Some(pos);
+						Tile::Traversable
+					}
+					'X' => {
+						droid = Some(pos);
+						Tile::Traversable
+					}
+					'O' => {
+						oxygen_system = Some(pos);
+						Tile::Traversable
+					}
+					_ => {
+						return Err(MapError::InvalidGlyph {
+							line: y + 1,
+							column: x + 1,
+							glyph,
+						})
+					}
+				};
+				world.insert(pos, tile);
+			}
+			width = width.max(line.chars().count() as i32);
+		}
+
+		let start = positions
+			.start
+			.or(start)
+			.or(droid)
+			.ok_or(MapError::NoStart)?;
+		let droid = positions.droid.or(droid).unwrap_or(start);
+		world.insert(droid, Tile::Droid);
+		let bottom_right = (top_left.0 + width - 1, top_left.1 + lines.len() as i32 - 1);
+		let size = |value: i32| value.max(0) as u32;
+		Ok(Self {
+			droid_starting_pos: start,
+			droid_pos: droid,
+			oxygen_system_pos: positions.oxygen_system.or(oxygen_system),
+			world,
+			world_size: vec![
+				(Direction::North, size(-top_left.1)),
+				(Direction::South, size(bottom_right.1)),
+				(Direction::West, size(-top_left.0)),
+				(Direction::East, size(bottom_right.0)),
+			]
+			.into_iter()
+			.collect(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{maze::Maze, Controller};
+
+	#[test]
+	fn a_saved_map_loads_the_same() {
+		let mut maze = Maze::generate(21, 11, crate::maze::MazeKind::Braided, 5);
+		let result = Controller::new().run(&mut maze).unwrap();
+		let mut file = Vec::new();
+		result.map.save(&mut file).unwrap();
+		let map = GameState::load(&file[..]).unwrap();
+		assert_eq!(map.world, result.map.world);
+		assert_eq!(map.droid_starting_pos, (0, 0));
+		assert_eq!(map.oxygen_system_pos, result.oxygen_system);
+		assert_eq!(map.extent(), result.map.extent());
+	}
+
+	#[test]
+	fn the_droid_can_hide_the_start() {
+		let map: GameState = "#X.\nstart=0,0 droid=0,0 top-left=-1,0".parse().unwrap();
+		assert_eq!(map.droid_pos(), (0, 0));
+		assert_eq!(map.world[&(0, 0)], Tile::Droid);
+		assert_eq!(map.world[&(1, 0)], Tile::Traversable);
+		assert_eq!(map.plain().to_string(), "#X.\n");
+	}
+
+	#[test]
+	fn exploration_continues_from_a_loaded_map() {
+		let mut maze: Maze = "#######\n#S....#\n#.###.#\n#...#O#\n#######"
+			.parse()
+			.unwrap();
+		// Only the start's surroundings have been seen.
+		let mut map: GameState = "###\n#S.\n#.#".parse().unwrap();
+		map.return_droid_to_start();
+		let result = Controller::resume(map).run(&mut maze).unwrap();
+		assert!(result.map.is_fully_explored());
+		assert_eq!(result.shortest_path, Some(6));
+	}
+
+	#[test]
+	fn it_points_at_invalid_glyphs() {
+		assert!(matches!(
+			"#S\n#!".parse::<GameState>(),
+			Err(MapError::InvalidGlyph {
+				line: 2,
+				column: 2,
+				glyph: '!'
+			})
+		));
+		assert!(matches!(
+			"#S\nstart=nowhere".parse::<GameState>(),
+			Err(MapError::InvalidPositions(_))
+		));
+		assert!(matches!("#.#".parse::<GameState>(), Err(MapError::NoStart)));
+	}
+}
@@ -0,0 +1,381 @@
+use std::{
+	collections::HashSet,
+	error::Error,
+	fmt,
+	io::{self, Read},
+};
+
+use crate::{io::Environment, Direction, ExplorationError};
+
+#[derive(Debug)]
+pub enum MazeError {
+	/// A character that's none of ` #.SO`, with its 1-based line and column.
+	InvalidGlyph {
+		line: usize,
+		column: usize,
+		glyph: char,
+	},
+	/// There's no `S` to start from, or more than one.
+	NoStart,
+	Io(io::Error),
+}
+
+impl fmt::Display for MazeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MazeError::InvalidGlyph {
+				line,
+				column,
+				glyph,
+			} => write!(
+				f,
+				"Unexpected `{}` in line {}, column {}",
+				glyph, line, column
+			),
+			MazeError::NoStart => write!(f, "The maze needs exactly one start `S`"),
+			MazeError::Io(e) => write!(f, "{}", e),
+		}
+	}
+}
+
+impl Error for MazeError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			MazeError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for MazeError {
+	fn from(e: io::Error) -> Self {
+		MazeError::Io(e)
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MazeKind {
+	/// Exactly one way between any two cells.
+	Perfect,
+	/// A perfect maze with its dead ends opened up, so there are loops.
+	Braided,
+}
+
+/// A maze to try exploration strategies on, without the Intcode program.
+///
+/// It answers movement commands just like the real droid, and counts them.
+///
+/// ## Examples
+/// ```
+/// # use day15::{maze::Maze, Controller};
+/// let mut maze: Maze = "#####\n#S..#\n#.#O#\n#####".parse().unwrap();
+/// let result = Controller::new().run(&mut maze).unwrap();
+/// assert_eq!(result.oxygen_system, Some((2, 1)));
+/// assert_eq!(result.shortest_path, Some(3));
+/// assert_eq!(maze.steps(), result.moves);
+/// assert_eq!(maze.coverage(), 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Maze {
+	open: HashSet<(i32, i32)>,
+	start: (i32, i32),
+	oxygen_system: Option<(i32, i32)>,
+	droid: (i32, i32),
+	visited: HashSet<(i32, i32)>,
+	steps: usize,
+	size: (i32, i32),
+}
+
+impl Maze {
+	fn new(
+		open: HashSet<(i32, i32)>,
+		start: (i32, i32),
+		oxygen_system: Option<(i32, i32)>,
+		size: (i32, i32),
+	) -> Self {
+		Self {
+			open,
+			start,
+			oxygen_system,
+			droid: start,
+			visited: vec![start].into_iter().collect(),
+			steps: 0,
+			size,
+		}
+	}
+
+	/// Read a maze drawn with `#` for walls, `.` for open cells, `S` for the start and `O` for
+	/// the oxygen system. Spaces and anything off the drawing count as walls.
+	pub fn load<R: Read>(mut reader: R) -> Result<Self, MazeError> {
+		let mut text = String::new();
+		reader.read_to_string(&mut text)?;
+		text.parse()
+	}
+
+	/// Generate a `width` by `height` maze, the same one for the same seed.
+	///
+	/// The maze is surrounded by walls, and walls separate its cells, so only odd positions
+	/// are cells. The droid starts in the top left, the oxygen system is somewhere else.
+	pub fn generate(width: usize, height: usize, kind: MazeKind, seed: u64) -> Self {
+		let (columns, rows) = ((width as i32 - 1) / 2, (height as i32 - 1) / 2);
+		let cell = |(column, row): (i32, i32)| (column * 2 + 1, row * 2 + 1);
+		let in_grid =
+			|(column, row): (i32, i32)| (0..columns).contains(&column) && (0..rows).contains(&row);
+		let mut rng = Rng::new(seed);
+		let mut open = HashSet::new();
+		if columns <= 0 || rows <= 0 {
+			open.insert((1, 1));
+			return Self::new(open, (1, 1), None, (width as i32, height as i32));
+		}
+
+		// Carve passages with a randomised depth-first search.
+		let mut stack = vec![(0, 0)];
+		open.insert(cell((0, 0)));
+		while let Some(&current) = stack.last() {
+			let unvisited: Vec<(i32, i32)> = Direction::ALL
+				.iter()
+				.map(|dir| dir.step(current))
+				.filter(|next| in_grid(*next) && !open.contains(&cell(*next)))
+				.collect();
+			if unvisited.is_empty() {
+				stack.pop();
+				continue;
+			}
+			let next = unvisited[rng.below(unvisited.len())];
+			let (from, to) = (cell(current), cell(next));
+			open.insert(((from.0 + to.0) / 2, (from.1 + to.1) / 2));
+			open.insert(to);
+			stack.push(next);
+		}
+
+		if kind == MazeKind::Braided {
+			for row in 0..rows {
+				for column in 0..columns {
+					let here = cell((column, row));
+					let walls: Vec<(i32, i32)> = Direction::ALL
+						.iter()
+						.filter(|dir| in_grid(dir.step((column, row))))
+						.map(|dir| dir.step(here))
+						.filter(|wall| !open.contains(wall))
+						.collect();
+					// A dead end has open passage on one side only.
+					let neighbours = Direction::ALL
+						.iter()
+						.filter(|dir| open.contains(&dir.step(here)))
+						.count();
+					if neighbours == 1 && !walls.is_empty() {
+						open.insert(walls[rng.below(walls.len())]);
+					}
+				}
+			}
+		}
+
+		let cells = (columns * rows) as usize;
+		let oxygen_system = match cells {
+			1 => None,
+			_ => {
+				let index = 1 + rng.below(cells - 1) as i32;
+				Some(cell((index % columns, index / columns)))
+			}
+		};
+		Self::new(open, (1, 1), oxygen_system, (width as i32, height as i32))
+	}
+
+	/// How many movement commands the maze has answered.
+	pub fn steps(&self) -> usize {
+		self.steps
+	}
+
+	/// The share of open cells the droid has been to, from 0 to 1.
+	pub fn coverage(&self) -> f64 {
+		self.visited.len() as f64 / self.open.len() as f64
+	}
+
+	pub fn open_cells(&self) -> usize {
+		self.open.len()
+	}
+
+	/// The droid's position, relative to where it started.
+	pub fn droid_pos(&self) -> (i32, i32) {
+		(self.droid.0 - self.start.0, self.droid.1 - self.start.1)
+	}
+
+	/// Put the droid back to the start and forget where it's been.
+	pub fn reset(&mut self) {
+		self.droid = self.start;
+		self.visited = vec![self.start].into_iter().collect();
+		self.steps = 0;
+	}
+}
+
+impl std::str::FromStr for Maze {
+	type Err = MazeError;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let mut open = HashSet::new();
+		let mut starts = Vec::new();
+		let mut oxygen_system = None;
+		let mut size = (0, 0);
+		for (y, line) in text.lines().enumerate() {
+			for (x, glyph) in line.chars().enumerate() {
+				let pos = (x as i32, y as i32);
+				match glyph {
+					'#' | ' ' => continue,
+					'.' => (),
+					'S' => starts.push(pos),
+					'O' => oxygen_system = Some(pos),
+					_ => {
+						return Err(MazeError::InvalidGlyph {
+							line: y + 1,
+							column: x + 1,
+							glyph,
+						})
+					}
+				}
+				open.insert(pos);
+			}
+			size = (size.0.max(line.chars().count() as i32), y as i32 + 1);
+		}
+		match starts[..] {
+			[start] => Ok(Self::new(open, start, oxygen_system, size)),
+			_ => Err(MazeError::NoStart),
+		}
+	}
+}
+
+/// Draws the maze in the format [`Maze::load`] reads.
+impl fmt::Display for Maze {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for y in 0..self.size.1 {
+			for x in 0..self.size.0 {
+				let glyph = if (x, y) == self.start {
+					'S'
+				} else if Some((x, y)) == self.oxygen_system {
+					'O'
+				} else if self.open.contains(&(x, y)) {
+					'.'
+				} else {
+					'#'
+				};
+				write!(f, "{}", glyph)?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl Environment for Maze {
+	fn send(&mut self, command: i128) -> Result<i128, ExplorationError> {
+		let dir = match command {
+			1 => Direction::North,
+			2 => Direction::South,
+			3 => Direction::West,
+			4 => Direction::East,
+			_ => return Err(ExplorationError::InvalidCommand(command)),
+		};
+		self.steps += 1;
+		let target = dir.step(self.droid);
+		if !self.open.contains(&target) {
+			return Ok(0);
+		}
+		self.droid = target;
+		self.visited.insert(target);
+		Ok(if Some(target) == self.oxygen_system {
+			2
+		} else {
+			1
+		})
+	}
+}
+
+/// A small xorshift generator, so mazes only depend on their seed.
+struct Rng(u64);
+
+impl Rng {
+	fn new(seed: u64) -> Self {
+		// Xorshift gets stuck on 0.
+		Self(seed ^ 0x9E37_79B9_7F4A_7C15)
+	}
+
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	/// A number from 0 up to, but not including, `bound`.
+	fn below(&mut self, bound: usize) -> usize {
+		(self.next() % bound as u64) as usize
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Controller;
+
+	#[test]
+	fn the_same_seed_makes_the_same_maze() {
+		let first = Maze::generate(21, 11, MazeKind::Perfect, 7);
+		let second = Maze::generate(21, 11, MazeKind::Perfect, 7);
+		let other = Maze::generate(21, 11, MazeKind::Perfect, 8);
+		assert_eq!(first.to_string(), second.to_string());
+		assert_ne!(first.to_string(), other.to_string());
+		assert_eq!(
+			first.to_string().parse::<Maze>().unwrap().to_string(),
+			first.to_string()
+		);
+	}
+
+	#[test]
+	fn a_perfect_maze_is_a_tree() {
+		let mut maze = Maze::generate(31, 21, MazeKind::Perfect, 1);
+		// 15 by 10 cells, plus the 149 passages that connect them without loops.
+		assert_eq!(maze.open_cells(), 150 + 149);
+		let result = Controller::new().run(&mut maze).unwrap();
+		assert_eq!(maze.coverage(), 1.0);
+		assert!(result.map.is_fully_explored());
+		assert!(result.shortest_path.is_some());
+	}
+
+	#[test]
+	fn a_braided_maze_has_no_dead_ends() {
+		let maze = Maze::generate(31, 21, MazeKind::Braided, 1);
+		for &pos in &maze.open {
+			let neighbours = Direction::ALL
+				.iter()
+				.filter(|dir| maze.open.contains(&dir.step(pos)))
+				.count();
+			assert!(neighbours >= 2, "Dead end at {:?}", pos);
+		}
+		let mut maze = maze;
+		let result = Controller::new().run(&mut maze).unwrap();
+		assert_eq!(maze.coverage(), 1.0);
+		assert_eq!(result.moves, maze.steps());
+	}
+
+	#[test]
+	fn it_points_at_invalid_glyphs() {
+		match "#S#\n#x#".parse::<Maze>() {
+			Err(MazeError::InvalidGlyph {
+				line: 2,
+				column: 2,
+				glyph: 'x',
+			}) => (),
+			other => panic!("{:?}", other.map(|maze| maze.to_string())),
+		}
+		assert!(matches!("###".parse::<Maze>(), Err(MazeError::NoStart)));
+	}
+
+	#[test]
+	fn it_rejects_unknown_commands() {
+		let mut maze: Maze = "#S.".parse().unwrap();
+		assert!(matches!(
+			maze.send(5),
+			Err(ExplorationError::InvalidCommand(5))
+		));
+		assert_eq!(maze.steps(), 0);
+	}
+}
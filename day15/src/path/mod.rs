@@ -0,0 +1,276 @@
+use std::{
+	cmp::Reverse,
+	collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use crate::{Direction, GameState, Tile};
+
+/// How to treat cells nobody has looked at yet.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Unexplored {
+	/// Never go through them.
+	Blocked,
+	/// Assume they're open, at this cost per step instead of 1.
+	Optimistic(u32),
+}
+
+/// A way from one cell to another, both ends included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+	pub cells: Vec<(i32, i32)>,
+	/// The sum of the step costs, which is the number of steps unless it crosses unexplored cells.
+	pub cost: u32,
+}
+
+impl Path {
+	pub fn len(&self) -> usize {
+		self.cells.len() - 1
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The moves that take the droid along the path.
+	pub fn directions(&self) -> Vec<Direction> {
+		self.cells
+			.windows(2)
+			.map(|pair| Direction::between(pair[0], pair[1]).expect("Path cells are adjacent"))
+			.collect()
+	}
+}
+
+/// Finds paths through what's known of the map.
+///
+/// Walls are always blocked. Unexplored cells only count as far as one step beyond what's been
+/// seen, so searches always end.
+///
+/// ## Examples
+/// ```
+/// # use day15::{path::{Pathfinder, Unexplored}, Controller, Direction};
+/// let game = Controller::new().game().clone();
+/// // All that's known is the starting cell.
+/// assert_eq!(Pathfinder::new(&game).bfs((0, 0), (1, 0)), None);
+///
+/// let optimistic = Pathfinder::new(&game).unexplored(Unexplored::Optimistic(5));
+/// let path = optimistic.a_star((0, 0), (1, 0)).unwrap();
+/// assert_eq!(path.cost, 5);
+/// assert_eq!(path.directions(), vec![Direction::East]);
+/// ```
+pub struct Pathfinder<'a> {
+	game: &'a GameState,
+	unexplored: Unexplored,
+	bounds: ((i32, i32), (i32, i32)),
+}
+
+impl<'a> Pathfinder<'a> {
+	/// A pathfinder that keeps to known cells.
+	pub fn new(game: &'a GameState) -> Self {
+		let (mut min, mut max) = (game.droid_pos, game.droid_pos);
+		for &(x, y) in game.world.keys() {
+			min = (min.0.min(x), min.1.min(y));
+			max = (max.0.max(x), max.1.max(y));
+		}
+		Self {
+			game,
+			unexplored: Unexplored::Blocked,
+			bounds: ((min.0 - 1, min.1 - 1), (max.0 + 1, max.1 + 1)),
+		}
+	}
+
+	pub fn unexplored(mut self, unexplored: Unexplored) -> Self {
+		self.unexplored = unexplored;
+		self
+	}
+
+	/// What it costs to step onto `pos`, or `None` if it can't be entered.
+	fn cost(&self, pos: (i32, i32)) -> Option<u32> {
+		let (min, max) = self.bounds;
+		if !(min.0..=max.0).contains(&pos.0) || !(min.1..=max.1).contains(&pos.1) {
+			return None;
+		}
+		match (
+			self.game.world.get(&pos).copied().unwrap_or_default(),
+			self.unexplored,
+		) {
+			(Tile::Unexplored, Unexplored::Optimistic(cost)) => Some(cost),
+			(tile, _) if tile.is_open() => Some(1),
+			_ => None,
+		}
+	}
+
+	fn neighbours(&self, pos: (i32, i32)) -> impl Iterator<Item = ((i32, i32), u32)> + '_ {
+		Direction::ALL.iter().filter_map(move |dir| {
+			let next = dir.step(pos);
+			self.cost(next).map(|cost| (next, cost))
+		})
+	}
+
+	/// The path with the fewest steps, whatever they cost.
+	pub fn bfs(&self, from: (i32, i32), to: (i32, i32)) -> Option<Path> {
+		if from == to {
+			return Some(self.path(&HashMap::new(), to));
+		}
+		self.bfs_through(from, |pos| pos == to && self.cost(pos).is_some())
+	}
+
+	/// The cheapest path.
+	pub fn dijkstra(&self, from: (i32, i32), to: (i32, i32)) -> Option<Path> {
+		self.cheapest(from, to, |_| 0)
+	}
+
+	/// The cheapest path, like [`Pathfinder::dijkstra`], but heading for `to` first.
+	pub fn a_star(&self, from: (i32, i32), to: (i32, i32)) -> Option<Path> {
+		// No step is cheaper than this, so the estimate never overshoots.
+		let cheapest_step = match self.unexplored {
+			Unexplored::Optimistic(cost) => cost.min(1),
+			Unexplored::Blocked => 1,
+		};
+		self.cheapest(from, to, |pos| {
+			((pos.0 - to.0).unsigned_abs() + (pos.1 - to.1).unsigned_abs()) * cheapest_step
+		})
+	}
+
+	/// The shortest path from `from` to the closest unexplored cell, through known open cells.
+	pub fn nearest_frontier(&self, from: (i32, i32)) -> Option<Path> {
+		Self {
+			game: self.game,
+			unexplored: Unexplored::Blocked,
+			bounds: self.bounds,
+		}
+		.bfs_through(from, |pos| self.game.is_unexplored(pos))
+	}
+
+	/// A breadth-first search that may end on a goal it can't otherwise walk through.
+	fn bfs_through(&self, from: (i32, i32), is_goal: impl Fn((i32, i32)) -> bool) -> Option<Path> {
+		let mut came_from = HashMap::new();
+		came_from.insert(from, from);
+		let mut queue: VecDeque<(i32, i32)> = vec![from].into_iter().collect();
+		while let Some(pos) = queue.pop_front() {
+			for dir in Direction::ALL.iter() {
+				let next = dir.step(pos);
+				if came_from.contains_key(&next) {
+					continue;
+				}
+				if is_goal(next) {
+					came_from.insert(next, pos);
+					return Some(self.path(&came_from, next));
+				}
+				if self.cost(next).is_some() {
+					came_from.insert(next, pos);
+					queue.push_back(next);
+				}
+			}
+		}
+		None
+	}
+
+	fn cheapest(
+		&self,
+		from: (i32, i32),
+		to: (i32, i32),
+		estimate: impl Fn((i32, i32)) -> u32,
+	) -> Option<Path> {
+		let mut costs = HashMap::new();
+		let mut came_from = HashMap::new();
+		costs.insert(from, 0);
+		came_from.insert(from, from);
+		let mut open = BinaryHeap::new();
+		open.push(Reverse((estimate(from), 0, from)));
+		while let Some(Reverse((_, cost, pos))) = open.pop() {
+			if pos == to {
+				return Some(self.path(&came_from, pos));
+			}
+			if cost > costs[&pos] {
+				// Already reached more cheaply.
+				continue;
+			}
+			for (next, step) in self.neighbours(pos) {
+				let next_cost = cost + step;
+				let cheaper = match costs.get(&next) {
+					Some(&known) => next_cost < known,
+					None => true,
+				};
+				if cheaper {
+					costs.insert(next, next_cost);
+					came_from.insert(next, pos);
+					open.push(Reverse((next_cost + estimate(next), next_cost, next)));
+				}
+			}
+		}
+		None
+	}
+
+	/// Walk back from `to` to where the search started.
+	fn path(&self, came_from: &HashMap<(i32, i32), (i32, i32)>, to: (i32, i32)) -> Path {
+		let mut cells = vec![to];
+		while let Some(&previous) = came_from.get(cells.last().unwrap()) {
+			if previous == *cells.last().unwrap() {
+				break;
+			}
+			cells.push(previous);
+		}
+		cells.reverse();
+		let cost = cells[1..]
+			.iter()
+			.map(|&pos| self.cost(pos).unwrap_or(1))
+			.sum();
+		Path { cells, cost }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::game_from_map;
+
+	#[test]
+	fn all_searches_agree_on_known_cells() {
+		let game = game_from_map(
+			"#######\n\
+			 #S....#\n\
+			 #.###.#\n\
+			 #...#O#\n\
+			 #######",
+		);
+		let pathfinder = Pathfinder::new(&game);
+		let (from, to) = ((1, 1), (5, 3));
+		let bfs = pathfinder.bfs(from, to).unwrap();
+		assert_eq!(bfs.len(), 6);
+		assert_eq!(bfs.cost, 6);
+		assert_eq!(pathfinder.dijkstra(from, to).unwrap().cost, 6);
+		assert_eq!(pathfinder.a_star(from, to).unwrap().cost, 6);
+		use Direction::*;
+		assert_eq!(bfs.directions(), vec![East, East, East, East, South, South]);
+		assert_eq!(pathfinder.bfs(from, (3, 2)), None);
+	}
+
+	#[test]
+	fn unexplored_cells_cost_what_they_are_told() {
+		// The row above is unexplored, the way round below is known.
+		let game = game_from_map("#S#O#\n#...#\n#####");
+		let (from, to) = ((1, 0), (3, 0));
+		let known = Pathfinder::new(&game).dijkstra(from, to).unwrap();
+		assert_eq!((known.len(), known.cost), (4, 4));
+		assert_eq!(known.cells[1], (1, 1));
+
+		let free = Pathfinder::new(&game).unexplored(Unexplored::Optimistic(0));
+		let path = free.a_star(from, to).unwrap();
+		assert_eq!((path.len(), path.cost), (4, 1));
+		assert_eq!(path.cells[1], (1, -1));
+
+		let dear = Pathfinder::new(&game).unexplored(Unexplored::Optimistic(10));
+		assert_eq!(dear.dijkstra(from, to), Some(known));
+	}
+
+	#[test]
+	fn the_nearest_frontier_is_next_to_a_known_cell() {
+		let game = game_from_map("####\n#S.\n####");
+		let path = Pathfinder::new(&game).nearest_frontier((1, 1)).unwrap();
+		assert_eq!(path.cells, vec![(1, 1), (2, 1), (3, 1)]);
+		assert_eq!(path.directions(), vec![Direction::East, Direction::East]);
+
+		let game = game_from_map("###\n#S#\n###");
+		assert_eq!(Pathfinder::new(&game).nearest_frontier((1, 1)), None);
+	}
+}
@@ -0,0 +1,231 @@
+use std::{
+	fs::{self, File},
+	io::{self, Write},
+	path::PathBuf,
+	str::FromStr,
+	thread,
+	time::{Duration, Instant},
+};
+
+use crate::GameState;
+
+/// When to draw the map while the droid explores.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Policy {
+	/// After every this many moves.
+	EveryMoves(usize),
+	/// After every move, this many times a second. Exploring waits for the next frame, so every
+	/// move is shown.
+	Fps(f64),
+	/// Whenever the droid has found out about a cell it didn't know before.
+	OnDiscovery,
+	Never,
+}
+
+impl FromStr for Policy {
+	type Err = String;
+
+	/// `every:<moves>`, `fps:<frames>`, `discovery` or `never`.
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("Unknown render policy `{}`", text);
+		Ok(match text.split_once(':') {
+			Some(("every", moves)) => match moves.parse().map_err(|_| invalid())? {
+				0 => return Err(invalid()),
+				moves => Policy::EveryMoves(moves),
+			},
+			Some(("fps", fps)) => match fps.parse().map_err(|_| invalid())? {
+				fps if fps > 0.0 && frame_interval(fps).is_some() => Policy::Fps(fps),
+				_ => return Err(invalid()),
+			},
+			None if text == "discovery" => Policy::OnDiscovery,
+			None if text == "never" => Policy::Never,
+			_ => return Err(invalid()),
+		})
+	}
+}
+
+/// The time between frames at `fps` frames a second, if it's one a [`Duration`] can hold.
+fn frame_interval(fps: f64) -> Option<Duration> {
+	Duration::try_from_secs_f64(1.0 / fps).ok()
+}
+
+/// Where frames go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+	Stdout,
+	/// One numbered file per frame, `frame-00000.txt` and so on, in this directory, which is
+	/// created if need be.
+	Directory(PathBuf),
+}
+
+/// Draws the map as exploration goes on, as often as its [`Policy`] says.
+///
+/// ## Examples
+/// ```
+/// # use day15::{maze::Maze, render::{Policy, Renderer}, Controller};
+/// let mut maze: Maze = "#####\n#S..#\n#.#O#\n#####".parse().unwrap();
+/// let mut renderer = Renderer::new(Policy::Never);
+/// Controller::new()
+///     .run_with(&mut maze, |controller| {
+///         renderer.update(controller.game(), controller.moves())
+///     })
+///     .unwrap();
+/// assert_eq!(renderer.frames(), 0);
+/// ```
+pub struct Renderer {
+	policy: Policy,
+	colour: bool,
+	target: Target,
+	frames: usize,
+	last_drawn: Option<Instant>,
+	known_cells: usize,
+}
+
+impl Renderer {
+	/// A renderer that prints to stdout, in colour.
+	pub fn new(policy: Policy) -> Self {
+		Self {
+			policy,
+			colour: true,
+			target: Target::Stdout,
+			frames: 0,
+			last_drawn: None,
+			known_cells: 0,
+		}
+	}
+
+	pub fn colour(mut self, colour: bool) -> Self {
+		self.colour = colour;
+		self
+	}
+
+	pub fn target(mut self, target: Target) -> Self {
+		self.target = target;
+		self
+	}
+
+	/// How many frames have been drawn.
+	pub fn frames(&self) -> usize {
+		self.frames
+	}
+
+	/// Whether the game is due to be drawn after `moves` moves.
+	pub fn is_due(&self, game: &GameState, moves: usize) -> bool {
+		match self.policy {
+			Policy::EveryMoves(every) => every != 0 && moves % every == 0,
+			Policy::Fps(_) => true,
+			Policy::OnDiscovery => game.world.len() > self.known_cells,
+			Policy::Never => false,
+		}
+	}
+
+	/// Draw the game if it's due, waiting for the next frame first if the policy sets a frame
+	/// rate.
+	pub fn update(&mut self, game: &GameState, moves: usize) -> io::Result<()> {
+		let due = self.is_due(game, moves);
+		self.known_cells = game.world.len();
+		if due {
+			if let Some(wait) = self.until_next_frame() {
+				thread::sleep(wait);
+			}
+			self.draw(game)?;
+		}
+		Ok(())
+	}
+
+	/// How long it is until the next frame is due at the policy's frame rate.
+	fn until_next_frame(&self) -> Option<Duration> {
+		match (self.policy, self.last_drawn) {
+			(Policy::Fps(fps), Some(time)) => frame_interval(fps)?.checked_sub(time.elapsed()),
+			_ => None,
+		}
+	}
+
+	/// Draw the game now, whatever the policy.
+	pub fn draw(&mut self, game: &GameState) -> io::Result<()> {
+		let frame = if self.colour {
+			game.to_string()
+		} else {
+			game.plain().to_string()
+		};
+		match &self.target {
+			Target::Stdout => println!("{}", frame),
+			Target::Directory(dir) => {
+				fs::create_dir_all(dir)?;
+				let path = dir.join(format!("frame-{:05}.txt", self.frames));
+				File::create(path)?.write_all(frame.as_bytes())?;
+			}
+		}
+		self.frames += 1;
+		self.last_drawn = Some(Instant::now());
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::game_from_map;
+
+	#[test]
+	fn it_parses_policies() {
+		assert_eq!("every:10".parse(), Ok(Policy::EveryMoves(10)));
+		assert_eq!("fps:2.5".parse(), Ok(Policy::Fps(2.5)));
+		assert_eq!("discovery".parse(), Ok(Policy::OnDiscovery));
+		assert_eq!("never".parse(), Ok(Policy::Never));
+		assert!("every:0".parse::<Policy>().is_err());
+		assert!("fps:-1".parse::<Policy>().is_err());
+		assert!("fps:1e-300".parse::<Policy>().is_err());
+		assert!("fps:NaN".parse::<Policy>().is_err());
+		assert!("sometimes".parse::<Policy>().is_err());
+	}
+
+	#[test]
+	fn it_draws_when_due() {
+		let game = game_from_map("#S.");
+		let every = Renderer::new(Policy::EveryMoves(3));
+		assert!(!every.is_due(&game, 2));
+		assert!(every.is_due(&game, 3));
+
+		let mut discovery = Renderer::new(Policy::OnDiscovery);
+		assert!(discovery.is_due(&game, 1));
+		discovery.known_cells = 3;
+		assert!(!discovery.is_due(&game, 2));
+
+		let mut fps = Renderer::new(Policy::Fps(0.001));
+		assert!(fps.is_due(&game, 1));
+		assert_eq!(fps.until_next_frame(), None);
+		fps.last_drawn = Some(Instant::now());
+		assert!(fps.is_due(&game, 2));
+		assert!(fps.until_next_frame().unwrap() > Duration::from_secs(900));
+	}
+
+	#[test]
+	fn frames_are_paced() {
+		let dir = std::env::temp_dir().join(format!("day15-paced-{}", std::process::id()));
+		let game = game_from_map("#S.");
+		let mut renderer = Renderer::new(Policy::Fps(20.0)).target(Target::Directory(dir.clone()));
+		let start = Instant::now();
+		for moves in 1..=3 {
+			renderer.update(&game, moves).unwrap();
+		}
+		assert_eq!(renderer.frames(), 3);
+		assert!(start.elapsed() >= Duration::from_millis(100));
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn frames_go_to_numbered_files() {
+		let dir = std::env::temp_dir().join(format!("day15-frames-{}", std::process::id()));
+		let game = game_from_map("#S.");
+		let mut renderer = Renderer::new(Policy::EveryMoves(1))
+			.colour(false)
+			.target(Target::Directory(dir.clone()));
+		renderer.update(&game, 1).unwrap();
+		renderer.update(&game, 2).unwrap();
+		assert_eq!(renderer.frames(), 2);
+		let frame = std::fs::read_to_string(dir.join("frame-00001.txt")).unwrap();
+		assert_eq!(frame, game.plain().to_string());
+		std::fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -0,0 +1,34 @@
+//! Translates the Intcode puzzle inputs in this repository and the programs in
+//! `tests/programs` ahead of time, so that `tests/aot.rs` can check the translations
+//! against the interpreter.
+
+use std::{env, fs, path::Path};
+
+use day09::{aot, loader};
+
+const PROGRAMS: [(&str, &str); 6] = [
+	("day02", "../day02/input.txt"),
+	("day05", "../day05/input.txt"),
+	("day09", "../day09/input.txt"),
+	("day13", "../day13/input.txt"),
+	(
+		"read_out_of_bounds",
+		"tests/programs/read_out_of_bounds.txt",
+	),
+	(
+		"write_out_of_bounds",
+		"tests/programs/write_out_of_bounds.txt",
+	),
+];
+
+fn main() {
+	let out_dir = env::var("OUT_DIR").expect("Cargo sets OUT_DIR");
+	for (name, path) in PROGRAMS.iter() {
+		println!("cargo:rerun-if-changed={}", path);
+		let file = fs::File::open(path).unwrap_or_else(|e| panic!("Can't open {}: {}", path, e));
+		let program = loader::load(file).unwrap_or_else(|e| panic!("Can't load {}: {}", path, e));
+		let destination = Path::new(&out_dir).join(format!("{}.rs", name));
+		fs::write(&destination, aot::translate(&program))
+			.unwrap_or_else(|e| panic!("Can't write {}: {}", destination.display(), e));
+	}
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::time::{Duration, Instant};
+
+use day09::coverage::{execute_step_with_coverage, Coverage};
+use day09::disasm::{disassemble, mnemonic, Instruction};
+use day09::io::{ValueReader, ValueWriter};
+use day09::memory::{diff, Dump};
+use day09::{aot, execute_step, loader, Opcode, State};
+
+use crate::{Command, Options};
+
+pub fn execute(options: &Options) -> Result<(), Box<dyn Error>> {
+	let image = loader::load(fs::File::open(&options.program_path)?)?;
+	let image_len = image.len();
+	let mut program = image;
+	if program.len() < options.memory_size {
+		program.resize(options.memory_size, 0);
+	}
+	options.patch.apply(&mut program)?;
+
+	match options.command {
+		Command::Run => run(program, options),
+		Command::Disasm => disasm(&program),
+		Command::Trace => trace(program, options),
+		Command::Profile => profile(program, options),
+		Command::Patch => patch(&program, image_len),
+		Command::Bench => bench(program, options),
+		Command::Translate => translate(&program[..image_len]),
+		Command::Dump => dump(&program),
+		Command::Diff => diff_with(&program, options),
+	}
+}
+
+fn input(options: &Options) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+	let source: Box<dyn BufRead> = match &options.input_path {
+		Some(path) => Box::new(BufReader::new(fs::File::open(path)?)),
+		None => Box::new(BufReader::new(io::stdin())),
+	};
+	Ok(ascii_input(source, options.ascii))
+}
+
+fn output(options: &Options) -> Box<dyn Write> {
+	ascii_output(Box::new(io::stdout()), options.ascii)
+}
+
+/// In ASCII mode, every byte of input is a value of its own.
+fn ascii_input(source: Box<dyn BufRead>, ascii: bool) -> Box<dyn BufRead> {
+	if !ascii {
+		return source;
+	}
+	let mut bytes = source.bytes();
+	Box::new(ValueReader::new(move || {
+		bytes.next().transpose().map(|byte| byte.map(i128::from))
+	}))
+}
+
+/// In ASCII mode, values that are ASCII characters are printed as such, anything else as a number.
+fn ascii_output(mut sink: Box<dyn Write>, ascii: bool) -> Box<dyn Write> {
+	if !ascii {
+		return sink;
+	}
+	Box::new(ValueWriter::new(move |value| {
+		if (0..128).contains(&value) {
+			write!(sink, "{}", value as u8 as char)
+		} else {
+			writeln!(sink, "{}", value)
+		}
+	}))
+}
+
+fn run(mut program: Vec<i128>, options: &Options) -> Result<(), Box<dyn Error>> {
+	day09::execute_program(&mut program, input(options)?, output(options))?;
+	if let Some(path) = &options.dump_path {
+		fs::write(path, Dump::new(&program).to_string())?;
+	}
+	Ok(())
+}
+
+fn disasm(program: &[i128]) -> Result<(), Box<dyn Error>> {
+	let stdout = io::stdout();
+	let mut stdout = stdout.lock();
+	for line in disassemble(program) {
+		writeln!(stdout, "{}", line)?;
+	}
+	Ok(())
+}
+
+fn trace(mut program: Vec<i128>, options: &Options) -> Result<(), Box<dyn Error>> {
+	let (mut reader, mut writer) = (input(options)?, output(options));
+	let (mut idx, mut state) = (0, State::new());
+
+	loop {
+		match Instruction::decode(&program, idx) {
+			Some(instruction) => eprintln!(
+				"{:04}: {:<32} rb={}",
+				idx,
+				instruction.to_string(),
+				state.relative_base()
+			),
+			None => match program.get(idx) {
+				Some(value) => eprintln!("{:04}: data {}", idx, value),
+				None => eprintln!("{:04}: <out of range>", idx),
+			},
+		}
+		if let Opcode::Halt =
+			execute_step(&mut program, &mut idx, &mut state, &mut reader, &mut writer)?
+		{
+			return Ok(());
+		}
+	}
+}
+
+fn profile(mut program: Vec<i128>, options: &Options) -> Result<(), Box<dyn Error>> {
+	let original = program.clone();
+	let (mut reader, mut writer) = (input(options)?, output(options));
+	let (mut idx, mut state) = (0, State::new());
+	let mut coverage = Coverage::new();
+	let mut opcodes: HashMap<Opcode, u64> = HashMap::new();
+
+	let start = Instant::now();
+	loop {
+		let opcode = execute_step_with_coverage(
+			&mut program,
+			&mut idx,
+			&mut state,
+			&mut reader,
+			&mut writer,
+			&mut coverage,
+		)?;
+		*opcodes.entry(opcode).or_insert(0) += 1;
+		if opcode == Opcode::Halt {
+			break;
+		}
+	}
+	let elapsed = start.elapsed();
+	let steps: u64 = opcodes.values().sum();
+
+	eprintln!("{} steps in {:?}", steps, elapsed);
+	eprintln!("{}", coverage.summary(&original));
+
+	let mut opcodes = opcodes.into_iter().collect::<Vec<_>>();
+	opcodes.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+	eprintln!("\nBy opcode:");
+	for (opcode, count) in opcodes {
+		eprintln!(
+			"{:>12}  {:<4} {:5.1}%",
+			count,
+			mnemonic(opcode),
+			percent(count, steps)
+		);
+	}
+
+	let mut hottest = coverage
+		.executed_addresses()
+		.map(|address| (address, coverage.hits(address)))
+		.collect::<Vec<_>>();
+	hottest.sort_by_key(|(address, hits)| (std::cmp::Reverse(*hits), *address));
+	eprintln!("\nHottest instructions:");
+	for (address, hits) in hottest.into_iter().take(20) {
+		let instruction = Instruction::decode(&original, address)
+			.map_or_else(|| "?".to_string(), |instruction| instruction.to_string());
+		eprintln!(
+			"{:>12}  {:04}: {:<32} {:5.1}%",
+			hits,
+			address,
+			instruction,
+			percent(hits, steps)
+		);
+	}
+	Ok(())
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+	part as f64 * 100.0 / total.max(1) as f64
+}
+
+fn patch(program: &[i128], image_len: usize) -> Result<(), Box<dyn Error>> {
+	// Keep the program's own trailing zeros, but not the extra memory.
+	let end = program
+		.iter()
+		.rposition(|value| *value != 0)
+		.map_or(0, |last| last + 1)
+		.max(image_len);
+	let text = program[..end]
+		.iter()
+		.map(|value| value.to_string())
+		.collect::<Vec<String>>()
+		.join(",");
+	println!("{}", text);
+	Ok(())
+}
+
+fn bench(program: Vec<i128>, options: &Options) -> Result<(), Box<dyn Error>> {
+	// Every run gets the same input.
+	let mut input_bytes = Vec::new();
+	match &options.input_path {
+		Some(path) => fs::File::open(path)?.read_to_end(&mut input_bytes)?,
+		None => io::stdin().read_to_end(&mut input_bytes)?,
+	};
+
+	let mut timings = Vec::with_capacity(options.runs);
+	let mut steps = 0;
+	for _ in 0..options.runs {
+		let mut memory = program.clone();
+		let mut reader = ascii_input(
+			Box::new(io::Cursor::new(input_bytes.clone())),
+			options.ascii,
+		);
+		let mut writer = ascii_output(Box::new(io::sink()), options.ascii);
+		let (mut idx, mut state) = (0, State::new());
+		steps = 0;
+
+		let start = Instant::now();
+		loop {
+			steps += 1;
+			if let Opcode::Halt =
+				execute_step(&mut memory, &mut idx, &mut state, &mut reader, &mut writer)?
+			{
+				break;
+			}
+		}
+		timings.push(start.elapsed());
+	}
+
+	let total: Duration = timings.iter().sum();
+	let mean = total / options.runs.max(1) as u32;
+	println!("{} runs of {} steps each", options.runs, steps);
+	if let (Some(min), Some(max)) = (timings.iter().min(), timings.iter().max()) {
+		println!("min {:?}, mean {:?}, max {:?}", min, mean, max);
+		println!("{:.0} steps per second", steps as f64 / mean.as_secs_f64());
+	}
+	Ok(())
+}
+
+fn translate(image: &[i128]) -> Result<(), Box<dyn Error>> {
+	io::stdout().write_all(aot::translate(image).as_bytes())?;
+	Ok(())
+}
+
+fn dump(program: &[i128]) -> Result<(), Box<dyn Error>> {
+	io::stdout().write_all(Dump::new(program).to_string().as_bytes())?;
+	Ok(())
+}
+
+fn diff_with(program: &[i128], options: &Options) -> Result<(), Box<dyn Error>> {
+	let other_path = options
+		.other_path
+		.as_ref()
+		.ok_or("Missing program to compare with")?;
+	let other = loader::load(fs::File::open(other_path)?)?;
+
+	let stdout = io::stdout();
+	let mut stdout = stdout.lock();
+	for run in diff(program, &other) {
+		writeln!(stdout, "{}", run)?;
+	}
+	Ok(())
+}
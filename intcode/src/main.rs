@@ -0,0 +1,158 @@
+use day09::memory::{parse_assignment, Patch};
+use std::{env, error::Error, fs, process};
+
+mod commands;
+
+const USAGE: &str = "Usage: intcode <command> <program> [options]
+
+Commands:
+    run                 Run the program
+    disasm              Print the program's disassembly
+    trace               Run the program, printing every instruction to stderr
+    profile             Run the program and report its hottest instructions
+    patch <addr=value>  Print the program with the given memory cells replaced
+    bench               Run the program repeatedly and report timings
+    translate           Print the program translated into a Rust module
+    dump                Print the program's memory as a table of addresses and values
+    diff <other>        Print the memory cells that differ from another program or dump
+
+Options:
+    --memory <size>       Memory size [default: 65535]
+    --input <file>        Read input from a file instead of stdin
+    --ascii               Translate input and output to and from ASCII text
+    --set <addr=value>    Replace a memory cell before running, may be repeated
+    --patch <file>        Replace the memory cells listed in a file of addr=value lines
+    --dump <file>         After running, write the memory to a file in the format of `dump`
+    --runs <n>            Number of runs to benchmark [default: 10]";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Command {
+	Run,
+	Disasm,
+	Trace,
+	Profile,
+	Patch,
+	Bench,
+	Translate,
+	Dump,
+	Diff,
+}
+
+#[derive(Debug)]
+pub struct Options {
+	pub command: Command,
+	pub program_path: String,
+	pub memory_size: usize,
+	pub input_path: Option<String>,
+	pub ascii: bool,
+	pub patch: Patch,
+	pub runs: usize,
+	pub dump_path: Option<String>,
+	pub other_path: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, Box<dyn Error>> {
+	let command = match args.next().as_deref() {
+		Some("run") => Command::Run,
+		Some("disasm") => Command::Disasm,
+		Some("trace") => Command::Trace,
+		Some("profile") => Command::Profile,
+		Some("patch") => Command::Patch,
+		Some("bench") => Command::Bench,
+		Some("translate") => Command::Translate,
+		Some("dump") => Command::Dump,
+		Some("diff") => Command::Diff,
+		Some(other) => return Err(format!("Unknown command `{}`", other).into()),
+		None => return Err("Missing command".into()),
+	};
+	let program_path = args.next().ok_or("Missing program")?;
+	let other_path = match command {
+		Command::Diff => Some(args.next().ok_or("Missing program to compare with")?),
+		_ => None,
+	};
+
+	let mut options = Options {
+		command,
+		program_path,
+		memory_size: 0xFFFF,
+		input_path: None,
+		ascii: false,
+		patch: Patch::new(),
+		runs: 10,
+		dump_path: None,
+		other_path,
+	};
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or(format!("Missing value for `{}`", arg));
+		match arg.as_str() {
+			"--memory" => options.memory_size = value()?.parse()?,
+			"--input" => options.input_path = Some(value()?),
+			"--ascii" => options.ascii = true,
+			"--set" => {
+				let (address, value) = parse_assignment(&value()?)?;
+				options.patch.set(address, value);
+			}
+			"--patch" => options
+				.patch
+				.extend(&Patch::load(fs::File::open(value()?)?)?),
+			"--runs" => options.runs = value()?.parse()?,
+			"--dump" => options.dump_path = Some(value()?),
+			assignment if command == Command::Patch && !assignment.starts_with("--") => {
+				let (address, value) = parse_assignment(assignment)?;
+				options.patch.set(address, value);
+			}
+			other => return Err(format!("Unknown option `{}`", other).into()),
+		}
+	}
+
+	Ok(options)
+}
+
+fn main() {
+	let options = match parse_args(env::args().skip(1)) {
+		Ok(options) => options,
+		Err(e) => {
+			eprintln!("{}\n\n{}", e, USAGE);
+			process::exit(2);
+		}
+	};
+
+	if let Err(e) = commands::execute(&options) {
+		eprintln!("Error: {}", e);
+		process::exit(1);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+		line.split_whitespace().map(String::from)
+	}
+
+	#[test]
+	fn it_parses_options() {
+		let options = parse_args(args("run input.txt --memory 100 --ascii --set 0=2")).unwrap();
+		assert_eq!(options.command, Command::Run);
+		assert_eq!(options.program_path, "input.txt");
+		assert_eq!(options.memory_size, 100);
+		assert!(options.ascii);
+		assert_eq!(options.patch.cells(), &[(0, 2)]);
+	}
+
+	#[test]
+	fn it_takes_assignments_only_for_patch() {
+		let options = parse_args(args("patch input.txt 1=12 2=2")).unwrap();
+		assert_eq!(options.patch.cells(), &[(1, 12), (2, 2)]);
+		assert!(parse_args(args("run input.txt 1=12")).is_err());
+	}
+
+	#[test]
+	fn it_requires_two_programs_for_diff() {
+		let options = parse_args(args("diff before.txt after.txt")).unwrap();
+		assert_eq!(options.program_path, "before.txt");
+		assert_eq!(options.other_path.as_deref(), Some("after.txt"));
+		assert!(parse_args(args("diff before.txt")).is_err());
+	}
+}
@@ -0,0 +1,112 @@
+//! The translated puzzle inputs must behave exactly like the interpreter.
+
+use day09::memory::Patch;
+use day09::{loader, IntcodeError};
+use std::fs;
+
+mod translated_day02 {
+	include!(concat!(env!("OUT_DIR"), "/day02.rs"));
+}
+mod translated_day05 {
+	include!(concat!(env!("OUT_DIR"), "/day05.rs"));
+}
+mod translated_day09 {
+	include!(concat!(env!("OUT_DIR"), "/day09.rs"));
+}
+mod translated_day13 {
+	include!(concat!(env!("OUT_DIR"), "/day13.rs"));
+}
+mod translated_read_out_of_bounds {
+	include!(concat!(env!("OUT_DIR"), "/read_out_of_bounds.rs"));
+}
+mod translated_write_out_of_bounds {
+	include!(concat!(env!("OUT_DIR"), "/write_out_of_bounds.rs"));
+}
+
+type Executor = fn(&mut [i128], &[u8], &mut Vec<u8>) -> Result<(), IntcodeError>;
+
+fn load(path: &str, patch: &str) -> Vec<i128> {
+	let mut program = loader::load(fs::File::open(path).unwrap()).unwrap();
+	program.resize(0xFFFF, 0);
+	patch.parse::<Patch>().unwrap().apply(&mut program).unwrap();
+	program
+}
+
+/// Run both versions and compare their output and final memory.
+fn compare(path: &str, patch: &str, input: &str, translated: Executor) -> String {
+	let mut expected_memory = load(path, patch);
+	let mut expected_output = Vec::new();
+	day09::execute_program(&mut expected_memory, input.as_bytes(), &mut expected_output).unwrap();
+
+	let mut memory = load(path, patch);
+	let mut output = Vec::new();
+	translated(&mut memory, input.as_bytes(), &mut output).unwrap();
+
+	assert_eq!(
+		String::from_utf8(output.clone()).unwrap(),
+		String::from_utf8(expected_output).unwrap()
+	);
+	assert!(memory == expected_memory, "Memory differs");
+	String::from_utf8(output).unwrap()
+}
+
+/// Run both versions on a program that fails and compare their errors.
+fn compare_error(path: &str, translated: Executor) -> String {
+	let mut expected_memory = load(path, "");
+	let expected = day09::execute_program(&mut expected_memory, &b""[..], &mut Vec::new())
+		.expect_err("The interpreter should fail");
+
+	let mut memory = load(path, "");
+	let error = translated(&mut memory, &b""[..], &mut Vec::new())
+		.expect_err("The translation should fail");
+
+	assert_eq!(format!("{:?}", error), format!("{:?}", expected));
+	assert!(memory == expected_memory, "Memory differs");
+	format!("{:?}", error)
+}
+
+#[test]
+fn day02_matches_interpreter() {
+	compare("../day02/input.txt", "1=12, 2=2", "", |p, r, w| {
+		translated_day02::execute_program(p, r, w)
+	});
+}
+
+#[test]
+fn day05_matches_interpreter() {
+	for input in ["1\n", "5\n"].iter() {
+		compare("../day05/input.txt", "", input, |p, r, w| {
+			translated_day05::execute_program(p, r, w)
+		});
+	}
+}
+
+#[test]
+fn day09_matches_interpreter() {
+	let output = compare("../day09/input.txt", "", "1\n", |p, r, w| {
+		translated_day09::execute_program(p, r, w)
+	});
+	assert_eq!(output, "2494485073\n");
+	compare("../day09/input.txt", "", "2\n", |p, r, w| {
+		translated_day09::execute_program(p, r, w)
+	});
+}
+
+#[test]
+fn day13_matches_interpreter() {
+	compare("../day13/input.txt", "", "", |p, r, w| {
+		translated_day13::execute_program(p, r, w)
+	});
+}
+
+#[test]
+fn out_of_bounds_errors_match_interpreter() {
+	let error = compare_error("tests/programs/read_out_of_bounds.txt", |p, r, w| {
+		translated_read_out_of_bounds::execute_program(p, r, w)
+	});
+	assert_eq!(error, "InvalidAddress(70000)");
+	let error = compare_error("tests/programs/write_out_of_bounds.txt", |p, r, w| {
+		translated_write_out_of_bounds::execute_program(p, r, w)
+	});
+	assert_eq!(error, "InvalidAddress(70000)");
+}